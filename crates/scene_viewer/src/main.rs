@@ -1,4 +1,8 @@
-use std::{fs::File, io::Read, path::Path, sync::Arc};
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use futures::executor::block_on;
 
@@ -9,12 +13,13 @@ use parking_lot::RwLock;
 use winit::{
     application::ApplicationHandler,
     dpi::PhysicalPosition,
-    event::WindowEvent,
+    event::{ElementState, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
+    keyboard::{Key, NamedKey},
     window::{Window, WindowAttributes},
 };
 
-use vide::{Quad, Scene, WinitRenderer};
+use vide::{Quad, Scene, SceneRecording, WinitRenderer};
 
 async fn create_renderer(window: Arc<Window>) -> WinitRenderer {
     WinitRenderer::new(window)
@@ -23,16 +28,82 @@ async fn create_renderer(window: Arc<Window>) -> WinitRenderer {
         .await
 }
 
+// Plays back a recording at the speed it was recorded. Space pauses and resumes, and the arrow
+// keys step a single frame forward or back.
+struct Replay {
+    recording: SceneRecording,
+    // Reused between frames so the recorded resources are only copied once
+    scene: Scene,
+    frame: usize,
+    playing: bool,
+    playback_start: Instant,
+    timestamp_at_start: Duration,
+}
+
+impl Replay {
+    fn new(recording: SceneRecording) -> Self {
+        let timestamp_at_start = recording.frames[0].timestamp;
+        Self {
+            recording,
+            scene: Scene::new(),
+            frame: 0,
+            playing: true,
+            playback_start: Instant::now(),
+            timestamp_at_start,
+        }
+    }
+
+    fn toggle_pause(&mut self) {
+        self.playing = !self.playing;
+        if self.playing {
+            if self.frame + 1 == self.recording.frames.len() {
+                self.frame = 0;
+            }
+            self.playback_start = Instant::now();
+            self.timestamp_at_start = self.recording.frames[self.frame].timestamp;
+        }
+    }
+
+    fn step_forward(&mut self) {
+        self.playing = false;
+        self.frame = (self.frame + 1).min(self.recording.frames.len() - 1);
+    }
+
+    fn step_back(&mut self) {
+        self.playing = false;
+        self.frame = self.frame.saturating_sub(1);
+    }
+
+    // Moves to the last frame which is due at the current time
+    fn advance(&mut self) {
+        if !self.playing {
+            return;
+        }
+
+        let timestamp = self.timestamp_at_start + self.playback_start.elapsed();
+        let frames = &self.recording.frames;
+        while self.frame + 1 < frames.len() && frames[self.frame + 1].timestamp <= timestamp {
+            self.frame += 1;
+        }
+
+        if self.frame + 1 == frames.len() {
+            self.playing = false;
+        }
+    }
+}
+
 struct App {
     scene: Arc<RwLock<Scene>>,
+    replay: Option<Replay>,
     renderer: Option<WinitRenderer>,
     mouse_pos: PhysicalPosition<f64>,
 }
 
 impl App {
-    fn new(scene: Arc<RwLock<Scene>>) -> Self {
+    fn new(scene: Arc<RwLock<Scene>>, replay: Option<Replay>) -> Self {
         App {
             scene,
+            replay,
             renderer: None,
             mouse_pos: PhysicalPosition::default(),
         }
@@ -52,8 +123,43 @@ impl ApplicationHandler for App {
                 self.mouse_pos = position;
                 self.renderer.as_ref().unwrap().window.request_redraw();
             }
+            WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+                let Some(replay) = &mut self.replay else {
+                    return;
+                };
+
+                match event.logical_key {
+                    Key::Named(NamedKey::Space) => replay.toggle_pause(),
+                    Key::Named(NamedKey::ArrowRight) => replay.step_forward(),
+                    Key::Named(NamedKey::ArrowLeft) => replay.step_back(),
+                    _ => return,
+                }
+                self.renderer.as_ref().unwrap().window.request_redraw();
+            }
             WindowEvent::RedrawRequested => {
-                let mut scene = self.scene.read().clone();
+                let mut live_scene;
+                let scene = match &mut self.replay {
+                    Some(replay) => {
+                        replay.advance();
+                        let window = &self.renderer.as_ref().unwrap().window;
+                        window.set_title(&format!(
+                            "Frame {} of {}{}",
+                            replay.frame + 1,
+                            replay.recording.frames.len(),
+                            if replay.playing { "" } else { " (paused)" }
+                        ));
+                        if replay.playing {
+                            window.request_redraw();
+                        }
+
+                        replay.recording.load_frame(replay.frame, &mut replay.scene);
+                        &mut replay.scene
+                    }
+                    None => {
+                        live_scene = self.scene.read().clone();
+                        &mut live_scene
+                    }
+                };
                 scene.add_layer(Default::default());
                 scene.add_quad(
                     Quad::new(
@@ -65,7 +171,7 @@ impl ApplicationHandler for App {
                     )
                     .with_edge_blur(5.0),
                 );
-                self.renderer.as_mut().unwrap().draw(&scene);
+                self.renderer.as_mut().unwrap().draw(scene);
             }
             WindowEvent::Resized(new_size) => {
                 self.renderer
@@ -107,42 +213,78 @@ fn main() {
     event_loop.set_control_flow(ControlFlow::Poll);
 
     let scene: Arc<RwLock<Scene>> = Arc::new(RwLock::new(Scene::new()));
-    let scene_path = Arc::from(Path::new("./scene.json"));
-    read_scene(&scene_path, &scene);
-
-    let mut watcher = recommended_watcher({
-        let scene_path = scene_path.clone();
-        let event_loop = event_loop.create_proxy();
-        let scene = scene.clone();
-        move |event| {
-            if let Ok(notify::event::Event {
-                kind: notify::event::EventKind::Modify(_),
-                ..
-            }) = event
-            {
-                read_scene(&scene_path, &scene);
-                event_loop.send_event(()).unwrap();
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let print_stats = match args.iter().position(|arg| arg == "--stats") {
+        Some(index) => {
+            args.remove(index);
+            true
+        }
+        None => false,
+    };
+    let mut args = args.into_iter();
+    let (mut app, _watcher) = match args.next() {
+        Some(flag) if flag == "--replay" => {
+            let path = args
+                .next()
+                .expect("Usage: scene_viewer --replay <recording>");
+            let recording = match SceneRecording::load(&path) {
+                Ok(recording) if !recording.frames.is_empty() => recording,
+                Ok(_) => {
+                    eprintln!("Recording {} contains no frames", path);
+                    return;
+                }
+                Err(error) => {
+                    eprintln!("Could not load {}: {}", path, error);
+                    return;
+                }
+            };
+            if print_stats {
+                println!("{}", recording.scene(0).stats());
             }
+            (App::new(scene, Some(Replay::new(recording))), None)
         }
-    })
-    .expect("Could not watch scene file");
+        scene_path => {
+            let scene_path = scene_path.unwrap_or_else(|| "./scene.json".to_string());
+            let scene_path: Arc<Path> = Arc::from(Path::new(&scene_path));
+            read_scene(&scene_path, &scene, print_stats);
+
+            let mut watcher = recommended_watcher({
+                let scene_path = scene_path.clone();
+                let event_loop = event_loop.create_proxy();
+                let scene = scene.clone();
+                move |event| {
+                    if let Ok(notify::event::Event {
+                        kind: notify::event::EventKind::Modify(_),
+                        ..
+                    }) = event
+                    {
+                        read_scene(&scene_path, &scene, print_stats);
+                        event_loop.send_event(()).unwrap();
+                    }
+                }
+            })
+            .expect("Could not watch scene file");
 
-    watcher
-        .watch(&scene_path, RecursiveMode::NonRecursive)
-        .unwrap();
+            watcher
+                .watch(&scene_path, RecursiveMode::NonRecursive)
+                .unwrap();
+            (App::new(scene, None), Some(watcher))
+        }
+    };
 
-    let mut app = App::new(scene);
     event_loop.run_app(&mut app).ok();
 }
 
-fn read_scene(path: &Path, scene: &RwLock<Scene>) {
-    let mut file = File::open(path).expect("Could not read file");
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)
-        .expect("Could not read file");
-
-    let new_scene: Scene = serde_json::from_str(&contents).expect("Could not parse scene file");
-
-    let mut scene = scene.write();
-    *scene = new_scene;
+fn read_scene(path: &Path, scene: &RwLock<Scene>, print_stats: bool) {
+    // Keep showing the last scene which loaded successfully so that a half written or broken
+    // scene file doesn't take down the viewer
+    match Scene::load(path) {
+        Ok(new_scene) => {
+            if print_stats {
+                println!("{}", new_scene.stats());
+            }
+            *scene.write() = new_scene;
+        }
+        Err(error) => eprintln!("Could not load {}: {}", path.display(), error),
+    }
 }
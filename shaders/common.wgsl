@@ -1,6 +1,14 @@
 const PI = 3.1415926535897932384626433832795;
 const FRAC_2_SQRT_PI = 1.12837916709551257389615890312154517;
 
+// Approximates the error function, used to integrate gaussian blurs in closed form
+fn compute_erf7(in: f32) -> f32 {
+    var x = in * FRAC_2_SQRT_PI;
+    let xx = x * x;
+    x += (0.24295 + (0.03395 + 0.0104 * xx) * xx) * (x * xx);
+    return x / sqrt(1.0 + x * x);
+}
+
 struct ShaderConstants {
     surface_size: vec2<f32>,
     atlas_size: vec2<f32>,
@@ -0,0 +1,99 @@
+#include "common.wgsl"
+
+struct InstancedEllipse {
+    color: vec4<f32>,
+    stroke_color: vec4<f32>,
+    center: vec2<f32>,
+    radii: vec2<f32>,
+    stroke_width: f32,
+}
+
+var<push_constant> constants: ShaderConstants;
+
+@group(0) @binding(0) var<storage> ellipses: array<InstancedEllipse>;
+@group(1) @binding(0) var surface: texture_2d<f32>;
+@group(1) @binding(1) var mask: texture_2d<f32>;
+@group(1) @binding(2) var texture_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) instance_index: u32,
+}
+
+@vertex
+fn vert(
+    @builtin(instance_index) instance_index: u32,
+    @builtin(vertex_index) vertex_index: u32,
+) -> VertexOutput {
+    var UNIT_QUAD_VERTICES: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
+        vec2(0.0, 0.0),
+        vec2(1.0, 0.0),
+        vec2(1.0, 1.0),
+        vec2(0.0, 0.0),
+        vec2(1.0, 1.0),
+        vec2(0.0, 1.0)
+    );
+
+    let unit_vertex_pos = UNIT_QUAD_VERTICES[vertex_index];
+    let instance = ellipses[instance_index];
+
+    // Covers the stroke plus a pixel for the anti aliased edge
+    let extent = instance.radii + vec2(instance.stroke_width / 2.0 + 1.0);
+    let vertex_pixel_pos = instance.center - extent + unit_vertex_pos * extent * 2.0;
+    let final_position =
+        vec2(0.0, 2.0) + min(vertex_pixel_pos, constants.surface_size) 
+        / constants.surface_size * vec2(1., -1.) * 2.0 - 1.0;
+
+    var out: VertexOutput;
+    out.instance_index = instance_index;
+    out.position = vec4(final_position, 0.0, 1.0);
+    return out;
+}
+
+// Approximates the signed distance to the ellipse edge by dividing its implicit function by the
+// length of its gradient. This is exact on the edge, which is all the anti aliasing and strokes of
+// a few pixels need.
+fn ellipse_distance(radii: vec2<f32>, relative_point: vec2<f32>) -> f32 {
+    let safe_radii = max(radii, vec2(0.0001));
+    let scaled = relative_point / safe_radii;
+    let gradient = scaled / safe_radii;
+    let scaled_length = length(scaled);
+    let gradient_length = length(gradient);
+    if (gradient_length <= 0.0) {
+        return -min(safe_radii.x, safe_radii.y);
+    }
+    return (scaled_length - 1.0) * scaled_length / gradient_length;
+}
+
+@fragment
+fn frag(
+    vertex_output: VertexOutput,
+) -> @location(0) vec4<f32> {
+    let instance = ellipses[vertex_output.instance_index];
+    let mask_color = textureSample(mask, texture_sampler, vertex_output.position.xy / constants.surface_size);
+    let point = vertex_output.position.xy;
+
+    let distance = ellipse_distance(instance.radii, point - instance.center);
+    let fill_alpha = instance.color.w * clamp(0.5 - distance, 0.0, 1.0);
+    var stroke_alpha = 0.0;
+    if (instance.stroke_width > 0.0) {
+        // Strokes thinner than a pixel fade out instead of breaking up
+        let half_width = instance.stroke_width / 2.0;
+        let coverage = clamp(0.5 - (abs(distance) - max(half_width, 0.5)), 0.0, 1.0);
+        stroke_alpha = instance.stroke_color.w * coverage * min(instance.stroke_width, 1.0);
+    }
+
+    // The stroke is drawn over the fill
+    let covered_fill_alpha = fill_alpha * (1.0 - stroke_alpha);
+    var result = vec4(0.0);
+    let alpha = stroke_alpha + covered_fill_alpha;
+    if (alpha > 0.0) {
+        result = vec4(
+            (instance.stroke_color.rgb * stroke_alpha + instance.color.rgb * covered_fill_alpha) / alpha,
+            alpha
+        );
+    }
+
+    result.w *= mask_color.w;
+    return result;
+}
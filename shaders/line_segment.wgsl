@@ -0,0 +1,114 @@
+#include "common.wgsl"
+
+struct InstancedLineSegment {
+    color: vec4<f32>,
+    start: vec2<f32>,
+    end: vec2<f32>,
+    width: f32,
+    // 0 for butt, 1 for round and 2 for square
+    cap: u32,
+    _padding: vec2<f32>,
+}
+
+var<push_constant> constants: ShaderConstants;
+
+@group(0) @binding(0) var<storage> segments: array<InstancedLineSegment>;
+@group(1) @binding(0) var surface: texture_2d<f32>;
+@group(1) @binding(1) var mask: texture_2d<f32>;
+@group(1) @binding(2) var texture_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) instance_index: u32,
+    // The distance along the line from the start and across it from the center
+    @location(1) line_position: vec2<f32>,
+}
+
+// The unit direction of the line, or along the x axis for zero length lines so their caps still
+// draw as dots
+fn line_direction(line: InstancedLineSegment) -> vec2<f32> {
+    let delta = line.end - line.start;
+    let line_length = length(delta);
+    if (line_length <= 0.0) {
+        return vec2(1.0, 0.0);
+    }
+    return delta / line_length;
+}
+
+@vertex
+fn vert(
+    @builtin(instance_index) instance_index: u32,
+    @builtin(vertex_index) vertex_index: u32,
+) -> VertexOutput {
+    var UNIT_QUAD_VERTICES: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
+        vec2(0.0, 0.0),
+        vec2(1.0, 0.0),
+        vec2(1.0, 1.0),
+        vec2(0.0, 0.0),
+        vec2(1.0, 1.0),
+        vec2(0.0, 1.0)
+    );
+
+    let unit_vertex_pos = UNIT_QUAD_VERTICES[vertex_index];
+    let instance = segments[instance_index];
+
+    let direction = line_direction(instance);
+    let normal = vec2(-direction.y, direction.x);
+    let line_length = length(instance.end - instance.start);
+
+    // Round and square caps extend past the end points by half the width, and every side gets an
+    // extra pixel for the anti aliased edge
+    let half_width = max(instance.width / 2.0, 0.5);
+    var cap_extension = 1.0;
+    if (instance.cap != 0u) {
+        cap_extension += half_width;
+    }
+    let line_position = vec2(
+        mix(-cap_extension, line_length + cap_extension, unit_vertex_pos.x),
+        mix(-half_width - 1.0, half_width + 1.0, unit_vertex_pos.y)
+    );
+    let vertex_pixel_pos =
+        instance.start + direction * line_position.x + normal * line_position.y;
+    let final_position =
+        vec2(0.0, 2.0) + vertex_pixel_pos / constants.surface_size * vec2(1., -1.) * 2.0 - 1.0;
+
+    var out: VertexOutput;
+    out.instance_index = instance_index;
+    out.line_position = line_position;
+    out.position = vec4(final_position, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn frag(
+    vertex_output: VertexOutput,
+) -> @location(0) vec4<f32> {
+    let instance = segments[vertex_output.instance_index];
+    let mask_color = textureSample(mask, texture_sampler, vertex_output.position.xy / constants.surface_size);
+
+    let line_length = length(instance.end - instance.start);
+    let along = vertex_output.line_position.x;
+    let across = abs(vertex_output.line_position.y);
+
+    // Lines thinner than a pixel are drawn a pixel wide and faded instead of breaking up
+    let half_width = max(instance.width / 2.0, 0.5);
+    let thin_fade = min(instance.width, 1.0);
+
+    var coverage = 0.0;
+    if (instance.cap == 1u) {
+        let distance = length(vec2(along - clamp(along, 0.0, line_length), across)) - half_width;
+        coverage = clamp(0.5 - distance, 0.0, 1.0);
+    } else {
+        var end_extension = 0.0;
+        if (instance.cap == 2u) {
+            end_extension = half_width;
+        }
+        let along_coverage = clamp(min(along, line_length - along) + end_extension + 0.5, 0.0, 1.0);
+        let across_coverage = clamp(half_width - across + 0.5, 0.0, 1.0);
+        coverage = along_coverage * across_coverage;
+    }
+
+    var result = instance.color;
+    result.w *= coverage * thin_fade * mask_color.w;
+    return result;
+}
@@ -2,11 +2,34 @@
 
 struct InstancedQuad {
     color: vec4<f32>,
-    _padding1: vec4<f32>,
+    // Top left, top right, bottom right, bottom left
+    corner_radii: vec4<f32>,
+    // Top, right, bottom, left
+    border_widths: vec4<f32>,
+    border_top_color: vec4<f32>,
+    border_right_color: vec4<f32>,
+    border_bottom_color: vec4<f32>,
+    border_left_color: vec4<f32>,
+    // Dash length, gap length, phase, and cap with 0 for butt, 1 for round and 2 for square. A
+    // zero gap draws a solid border
+    border_dash: vec4<f32>,
+    // Linear gradients store the start and end of the gradient line, radial gradients the center
+    // and radius
+    gradient_parameters: vec4<f32>,
+    gradient_offsets: vec4<f32>,
+    // Srgb encoded with 8 bits per channel
+    gradient_colors: vec4<u32>,
+    // The columns of the linear part of the transform, applied before the translation
+    transform: vec4<f32>,
+    transform_translation: vec2<f32>,
+    _padding: vec2<f32>,
     top_left: vec2<f32>,
     size: vec2<f32>,
-    _padding2: vec2<f32>,
-    corner_radius: f32,
+    // 0 for a solid fill, 1 for linear and 2 for radial
+    gradient_kind: u32,
+    // 0 for linear rgb, 1 for oklab
+    gradient_interpolation: u32,
+    gradient_stop_count: u32,
     edge_blur: f32,
 }
 
@@ -20,6 +43,8 @@ var<push_constant> constants: ShaderConstants;
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) instance_index: u32,
+    // The position in the quad's own coordinates before it is transformed
+    @location(1) local_position: vec2<f32>,
 }
 
 @vertex
@@ -40,30 +65,284 @@ fn vert(
     let instance = quads[instance_index];
 
     let blur_extension = vec2(max(instance.edge_blur, 0.0) * 3.0);
-    let vertex_pixel_pos =
+    let local_pos =
         (instance.top_left - blur_extension) + unit_vertex_pos * (instance.size + blur_extension * 2.0);
+    let matrix = mat2x2<f32>(instance.transform.xy, instance.transform.zw);
+    var vertex_pixel_pos = matrix * local_pos + instance.transform_translation;
+    var out: VertexOutput;
+    out.local_position = local_pos;
+    // Untransformed quads are clamped to the surface. Clamping a rotated or skewed quad would
+    // change its shape, so those are left for the rasterizer to clip.
+    if (all(instance.transform == vec4(1.0, 0.0, 0.0, 1.0))) {
+        vertex_pixel_pos = min(vertex_pixel_pos, constants.surface_size);
+        out.local_position = vertex_pixel_pos - instance.transform_translation;
+    }
     let final_position =
-        vec2(0.0, 2.0) + min(vertex_pixel_pos, constants.surface_size) 
-        / constants.surface_size * vec2(1., -1.) * 2.0 - 1.0;
+        vec2(0.0, 2.0) + vertex_pixel_pos / constants.surface_size * vec2(1., -1.) * 2.0 - 1.0;
 
-    var out: VertexOutput;
     out.instance_index = instance_index;
     out.position = vec4(final_position, 0.0, 1.0);
     return out;
 }
 
-fn quad_distance(quad: InstancedQuad, point: vec2<f32>) -> f32 {
-    let half_size = vec2(quad.size / 2.0 - quad.corner_radius);
-    let relative_point = point - (quad.top_left + quad.size / 2.0);
+// Picks the radius of the corner in the same quadrant as the point, relative to the rect center
+fn corner_radius(radii: vec4<f32>, relative_point: vec2<f32>) -> f32 {
+    let side = select(radii.xw, radii.yz, relative_point.x > 0.0);
+    return select(side.x, side.y, relative_point.y > 0.0);
+}
+
+fn rounded_rect_distance(top_left: vec2<f32>, size: vec2<f32>, radii: vec4<f32>, point: vec2<f32>) -> f32 {
+    let relative_point = point - (top_left + size / 2.0);
+    let radius = corner_radius(radii, relative_point);
+    let half_size = vec2(size / 2.0 - radius);
     let d = abs(relative_point) - half_size;
-    return length(max(d, vec2(0.0))) + min(max(d.x, d.y), 0.0) - quad.corner_radius;
+    return length(max(d, vec2(0.0))) + min(max(d.x, d.y), 0.0) - radius;
+}
+
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3(2.4));
+    return select(high, low, color <= vec3(0.04045));
+}
+
+fn linear_to_oklab(color: vec3<f32>) -> vec3<f32> {
+    let lms = mat3x3<f32>(
+        0.4122214708, 0.2119034982, 0.0883024619,
+        0.5363325363, 0.6806995451, 0.2817188376,
+        0.0514459929, 0.1073969566, 0.6299787005,
+    ) * color;
+    let lms_root = sign(lms) * pow(abs(lms), vec3(1.0 / 3.0));
+    return mat3x3<f32>(
+        0.2104542553, 1.9779984951, 0.0259040371,
+        0.7936177850, -2.4285922050, 0.7827717662,
+        -0.0040720468, 0.4505937099, -0.8086757660,
+    ) * lms_root;
 }
 
-fn compute_erf7(in: f32) -> f32 {
-    var x = in * FRAC_2_SQRT_PI;
-    let xx = x * x;
-    x += (0.24295 + (0.03395 + 0.0104 * xx) * xx) * (x * xx);
-    return x / sqrt(1.0 + x * x);
+fn oklab_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let lms_root = mat3x3<f32>(
+        1.0, 1.0, 1.0,
+        0.3963377774, -0.1055613458, -0.0894841775,
+        0.2158037573, -0.0638541728, -1.2914855480,
+    ) * color;
+    let lms = lms_root * lms_root * lms_root;
+    return mat3x3<f32>(
+        4.0767416621, -1.2684380046, -0.0041960863,
+        -3.3077115913, 2.6097574011, -0.7034186147,
+        0.2309699292, -0.3413193965, 1.7076147010,
+    ) * lms;
+}
+
+// Unpacks a gradient stop into premultiplied color in the interpolation space
+fn gradient_stop(quad: InstancedQuad, index: u32) -> vec4<f32> {
+    let color = unpack4x8unorm(quad.gradient_colors[index]);
+    var mixable = srgb_to_linear(color.rgb);
+    if (quad.gradient_interpolation == 1u) {
+        mixable = linear_to_oklab(mixable);
+    }
+    return vec4(mixable * color.a, color.a);
+}
+
+fn gradient_color(quad: InstancedQuad, point: vec2<f32>) -> vec4<f32> {
+    if (quad.gradient_stop_count == 0u) {
+        return vec4(0.0);
+    }
+
+    let parameters = quad.gradient_parameters;
+    var position = 0.0;
+    if (quad.gradient_kind == 1u) {
+        let line = parameters.zw - parameters.xy;
+        position = dot(point - parameters.xy, line) / max(dot(line, line), 0.0001);
+    } else {
+        position = length(point - parameters.xy) / max(parameters.z, 0.0001);
+    }
+
+    // Colors are mixed premultiplied so that transparent stops don't darken their neighbors
+    var mixed = gradient_stop(quad, 0u);
+    for (var index = 1u; index < quad.gradient_stop_count; index++) {
+        let start = quad.gradient_offsets[index - 1u];
+        let end = quad.gradient_offsets[index];
+        if (position > start) {
+            let amount = clamp((position - start) / max(end - start, 0.0001), 0.0, 1.0);
+            mixed = mix(gradient_stop(quad, index - 1u), gradient_stop(quad, index), amount);
+        }
+    }
+
+    if (mixed.a <= 0.0) {
+        return vec4(0.0);
+    }
+    var color = mixed.rgb / mixed.a;
+    if (quad.gradient_interpolation == 1u) {
+        color = oklab_to_linear(color);
+    }
+    return vec4(color, mixed.a);
+}
+
+// How much of the rounded rect covers the point, blurring the edge when the quad has an edge blur
+fn rounded_rect_alpha(
+    edge_blur: f32,
+    top_left: vec2<f32>,
+    size: vec2<f32>,
+    radii: vec4<f32>,
+    point: vec2<f32>,
+) -> f32 {
+    let distance = rounded_rect_distance(top_left, size, radii, point);
+    if (edge_blur > 0.0) {
+        let min_edge = min(size.x, size.y);
+        let inverse_blur = 1.0 / edge_blur;
+
+        let max_corner_radius = max(max(radii.x, radii.y), max(radii.z, radii.w));
+        let scale = 0.5
+            * compute_erf7(edge_blur * 0.5 * (max(size.x, size.y) - 0.5 * max_corner_radius));
+
+        return scale
+            * (compute_erf7(inverse_blur * (min_edge + distance))
+                - compute_erf7(inverse_blur * distance));
+    }
+    return select(0.0, 1.0, distance <= 0.0);
+}
+
+// Picks the side whose edge is nearest relative to its width, which splits the corners along the
+// line from the outer to the inner corner. Returns 0 for top, 1 for right, 2 for bottom and 3
+// for left.
+fn nearest_side(quad: InstancedQuad, point: vec2<f32>) -> u32 {
+    let from_top_left = point - quad.top_left;
+    let from_bottom_right = quad.top_left + quad.size - point;
+    let relative = vec4(from_top_left.y, from_bottom_right.x, from_bottom_right.y, from_top_left.x)
+        / max(quad.border_widths, vec4(0.0001));
+
+    var side = 0u;
+    var nearest = relative.x;
+    if (relative.y < nearest) {
+        side = 1u;
+        nearest = relative.y;
+    }
+    if (relative.z < nearest) {
+        side = 2u;
+        nearest = relative.z;
+    }
+    if (relative.w < nearest) {
+        side = 3u;
+    }
+    return side;
+}
+
+fn border_color(quad: InstancedQuad, point: vec2<f32>) -> vec4<f32> {
+    switch nearest_side(quad, point) {
+        case 0u: {
+            return quad.border_top_color;
+        }
+        case 1u: {
+            return quad.border_right_color;
+        }
+        case 2u: {
+            return quad.border_bottom_color;
+        }
+        default: {
+            return quad.border_left_color;
+        }
+    }
+}
+
+// How much of the dash pattern covers the point, or 1 for solid borders. The pattern runs
+// clockwise along the middle of the border from the end of the top left corner, so the distance
+// along it is measured on the edge or corner arc the point is closest to.
+fn dash_coverage(quad: InstancedQuad, point: vec2<f32>) -> f32 {
+    let dash = quad.border_dash;
+    let period = dash.x + dash.y;
+    if (dash.y <= 0.0 || period <= 0.0) {
+        return 1.0;
+    }
+
+    let widths = quad.border_widths;
+    let top_left = quad.top_left + widths.wx * 0.5;
+    let size = max(quad.size - (widths.wx + widths.yz) * 0.5, vec2(0.0));
+    let bottom_right = top_left + size;
+    let radii = min(
+        max(quad.corner_radii - max(widths.wxyz, widths.xyzw) * 0.5, vec4(0.0)),
+        vec4(min(size.x, size.y) * 0.5)
+    );
+
+    // Lengths of the straight edges and where each one starts along the pattern
+    let top = size.x - radii.x - radii.y;
+    let right = size.y - radii.y - radii.z;
+    let bottom = size.x - radii.z - radii.w;
+    let left = size.y - radii.w - radii.x;
+    let arcs = radii * (PI / 2.0);
+    let right_start = top + arcs.y;
+    let bottom_start = right_start + right + arcs.z;
+    let left_start = bottom_start + bottom + arcs.w;
+    let perimeter = left_start + left + arcs.x;
+
+    let top_left_center = top_left + radii.x;
+    let top_right_center = vec2(bottom_right.x - radii.y, top_left.y + radii.y);
+    let bottom_right_center = bottom_right - radii.z;
+    let bottom_left_center = vec2(top_left.x + radii.w, bottom_right.y - radii.w);
+
+    var along = 0.0;
+    var half_width = 0.0;
+    if (point.x < top_left_center.x && point.y < top_left_center.y) {
+        let offset = point - top_left_center;
+        along = left_start + left + atan2(-offset.y, -offset.x) * radii.x;
+        half_width = (widths.w + widths.x) * 0.25;
+    } else if (point.x > top_right_center.x && point.y < top_right_center.y) {
+        let offset = point - top_right_center;
+        along = top + atan2(offset.x, -offset.y) * radii.y;
+        half_width = (widths.x + widths.y) * 0.25;
+    } else if (point.x > bottom_right_center.x && point.y > bottom_right_center.y) {
+        let offset = point - bottom_right_center;
+        along = right_start + right + atan2(offset.y, offset.x) * radii.z;
+        half_width = (widths.y + widths.z) * 0.25;
+    } else if (point.x < bottom_left_center.x && point.y > bottom_left_center.y) {
+        let offset = point - bottom_left_center;
+        along = bottom_start + bottom + atan2(-offset.x, offset.y) * radii.w;
+        half_width = (widths.z + widths.w) * 0.25;
+    } else {
+        switch nearest_side(quad, point) {
+            case 0u: {
+                along = point.x - top_left_center.x;
+                half_width = widths.x * 0.5;
+            }
+            case 1u: {
+                along = right_start + point.y - top_right_center.y;
+                half_width = widths.y * 0.5;
+            }
+            case 2u: {
+                along = bottom_start + bottom_right_center.x - point.x;
+                half_width = widths.z * 0.5;
+            }
+            default: {
+                along = left_start + bottom_left_center.y - point.y;
+                half_width = widths.w * 0.5;
+            }
+        }
+    }
+
+    // Stretch the pattern so a whole number of periods fits around the quad
+    let scale = perimeter / (max(round(perimeter / period), 1.0) * period);
+    let on = dash.x * scale;
+    let scaled_period = period * scale;
+    let shifted = along - dash.z * scale;
+    let position = shifted - floor(shifted / scaled_period) * scaled_period;
+
+    // Signed distance along the border to the nearest dash, negative inside of one
+    var distance = max(-position, position - on);
+    if (position > on) {
+        distance = min(position - on, scaled_period - position);
+    }
+
+    if (dash.w > 1.5) {
+        distance -= half_width;
+    } else if (dash.w > 0.5) {
+        // Clamping the distance across the border leaves the sides of the dashes to the border's
+        // own antialiasing, so only the round ends are shaped here
+        let across = min(
+            abs(rounded_rect_distance(top_left, size, radii, point)),
+            max(half_width - 0.5, 0.0)
+        );
+        distance = length(vec2(max(distance, 0.0), across)) - half_width;
+    }
+    return clamp(0.5 - distance, 0.0, 1.0);
 }
 
 @fragment
@@ -72,27 +351,38 @@ fn frag(
 ) -> @location(0) vec4<f32> {
     let instance = quads[vertex_output.instance_index];
     let mask_color = textureSample(mask, texture_sampler, vertex_output.position.xy / constants.surface_size);
+    let point = vertex_output.local_position;
 
-    let distance = quad_distance(instance, vertex_output.position.xy);
-    var result = vec4(0.0);
-    if (instance.edge_blur > 0.0) {
-        let min_edge = min(instance.size.x, instance.size.y);
-        let inverse_blur = 1.0 / instance.edge_blur;
+    // The fill covers the quad inset by the border widths, with the corner radii shrunk so the
+    // inner edge of the border stays parallel to the outer edge
+    let widths = instance.border_widths;
+    let inner_top_left = instance.top_left + widths.wx;
+    let inner_size = max(instance.size - widths.wx - widths.yz, vec2(0.0));
+    let inner_radii = max(
+        instance.corner_radii - max(widths.wxyz, widths.xyzw),
+        vec4(0.0)
+    );
 
-        let scale = 0.5
-            * compute_erf7(instance.edge_blur * 0.5 * (max(instance.size.x, instance.size.y) - 0.5 * instance.corner_radius));
+    let outer_alpha = rounded_rect_alpha(
+        instance.edge_blur, instance.top_left, instance.size, instance.corner_radii, point);
+    let inner_alpha = rounded_rect_alpha(
+        instance.edge_blur, inner_top_left, inner_size, inner_radii, point);
 
-        let alpha = scale
-            * (compute_erf7(inverse_blur * (min_edge + distance))
-                - compute_erf7(inverse_blur * distance));
+    var fill = instance.color;
+    if (instance.gradient_kind != 0u) {
+        fill = gradient_color(instance, point);
+    }
+
+    let fill_alpha = fill.w * inner_alpha;
+    let border = border_color(instance, point);
+    let border_alpha = border.w * max(outer_alpha - inner_alpha, 0.0) * dash_coverage(instance, point);
 
-        result = instance.color;
-        result.w *= alpha;
-    } else if (distance <= 0.0) {
-        result = instance.color;
+    var result = vec4(0.0);
+    let alpha = fill_alpha + border_alpha;
+    if (alpha > 0.0) {
+        result = vec4((fill.rgb * fill_alpha + border.rgb * border_alpha) / alpha, alpha);
     }
 
     result.w *= mask_color.w;
     return result;
 }
-
@@ -0,0 +1,152 @@
+#include "common.wgsl"
+
+struct InstancedShadow {
+    color: vec4<f32>,
+    // Top left, top right, bottom right, bottom left
+    box_corner_radii: vec4<f32>,
+    shadow_corner_radii: vec4<f32>,
+    box_top_left: vec2<f32>,
+    box_size: vec2<f32>,
+    // The box after applying the offset and spread
+    shadow_top_left: vec2<f32>,
+    shadow_size: vec2<f32>,
+    // The standard deviation of the blur
+    sigma: f32,
+    // 1 for inset shadows, 0 for outset
+    inset: u32,
+    _padding: vec2<f32>,
+}
+
+var<push_constant> constants: ShaderConstants;
+
+@group(0) @binding(0) var<storage> shadows: array<InstancedShadow>;
+@group(1) @binding(0) var surface: texture_2d<f32>;
+@group(1) @binding(1) var mask: texture_2d<f32>;
+@group(1) @binding(2) var texture_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) instance_index: u32,
+}
+
+@vertex
+fn vert(
+    @builtin(instance_index) instance_index: u32,
+    @builtin(vertex_index) vertex_index: u32,
+) -> VertexOutput {
+    var UNIT_QUAD_VERTICES: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
+        vec2(0.0, 0.0),
+        vec2(1.0, 0.0),
+        vec2(1.0, 1.0),
+        vec2(0.0, 0.0),
+        vec2(1.0, 1.0),
+        vec2(0.0, 1.0)
+    );
+
+    let unit_vertex_pos = UNIT_QUAD_VERTICES[vertex_index];
+    let instance = shadows[instance_index];
+
+    // Inset shadows only draw inside of the box, outset shadows cover the blurred shape
+    var top_left = instance.box_top_left;
+    var size = instance.box_size;
+    if (instance.inset == 0u) {
+        let blur_extension = vec2(instance.sigma * 3.0);
+        top_left = instance.shadow_top_left - blur_extension;
+        size = instance.shadow_size + blur_extension * 2.0;
+    }
+
+    let vertex_pixel_pos = top_left + unit_vertex_pos * size;
+    let final_position =
+        vec2(0.0, 2.0) + min(vertex_pixel_pos, constants.surface_size) 
+        / constants.surface_size * vec2(1., -1.) * 2.0 - 1.0;
+
+    var out: VertexOutput;
+    out.instance_index = instance_index;
+    out.position = vec4(final_position, 0.0, 1.0);
+    return out;
+}
+
+// Picks the radius of the corner in the same quadrant as the point, relative to the rect center
+fn corner_radius(radii: vec4<f32>, relative_point: vec2<f32>) -> f32 {
+    let side = select(radii.xw, radii.yz, relative_point.x > 0.0);
+    return select(side.x, side.y, relative_point.y > 0.0);
+}
+
+fn rounded_rect_distance(top_left: vec2<f32>, size: vec2<f32>, radii: vec4<f32>, point: vec2<f32>) -> f32 {
+    let relative_point = point - (top_left + size / 2.0);
+    let radius = corner_radius(radii, relative_point);
+    let half_size = vec2(size / 2.0 - radius);
+    let d = abs(relative_point) - half_size;
+    return length(max(d, vec2(0.0))) + min(max(d.x, d.y), 0.0) - radius;
+}
+
+fn gaussian(x: f32, sigma: f32) -> f32 {
+    return exp(-(x * x) / (2.0 * sigma * sigma)) / (sqrt(2.0 * PI) * sigma);
+}
+
+// The blurred coverage of one horizontal slice of the rounded rect, which is the difference of two
+// erfs at the slice's left and right edges
+fn shadow_slice(x: f32, y: f32, sigma: f32, corner: f32, half_size: vec2<f32>) -> f32 {
+    let delta = min(half_size.y - corner - abs(y), 0.0);
+    let curved = half_size.x - corner + sqrt(max(0.0, corner * corner - delta * delta));
+    let scale = sqrt(0.5) / sigma;
+    return 0.5 * (compute_erf7((x + curved) * scale) - compute_erf7((x - curved) * scale));
+}
+
+// Gaussian blurred coverage of a rounded rect. The blur is separable, so the horizontal blur is
+// solved exactly per slice and only the vertical blur is integrated numerically with a few samples
+// within three standard deviations of the point.
+fn shadow_coverage(shadow: InstancedShadow, point: vec2<f32>) -> f32 {
+    if (shadow.sigma <= 0.0) {
+        let distance = rounded_rect_distance(
+            shadow.shadow_top_left, shadow.shadow_size, shadow.shadow_corner_radii, point);
+        return clamp(0.5 - distance, 0.0, 1.0);
+    }
+
+    let half_size = shadow.shadow_size / 2.0;
+    let relative_point = point - (shadow.shadow_top_left + half_size);
+    let corner = min(
+        corner_radius(shadow.shadow_corner_radii, relative_point),
+        min(half_size.x, half_size.y)
+    );
+
+    let low = relative_point.y - half_size.y;
+    let high = relative_point.y + half_size.y;
+    let start = clamp(-3.0 * shadow.sigma, low, high);
+    let end = clamp(3.0 * shadow.sigma, low, high);
+    let step = (end - start) / 4.0;
+
+    var y = start + step * 0.5;
+    var coverage = 0.0;
+    for (var i = 0; i < 4; i++) {
+        coverage += shadow_slice(relative_point.x, relative_point.y - y, shadow.sigma, corner, half_size)
+            * gaussian(y, shadow.sigma) * step;
+        y += step;
+    }
+    return coverage;
+}
+
+@fragment
+fn frag(
+    vertex_output: VertexOutput,
+) -> @location(0) vec4<f32> {
+    let instance = shadows[vertex_output.instance_index];
+    let mask_color = textureSample(mask, texture_sampler, vertex_output.position.xy / constants.surface_size);
+    let point = vertex_output.position.xy;
+
+    let box_distance = rounded_rect_distance(
+        instance.box_top_left, instance.box_size, instance.box_corner_radii, point);
+    let box_coverage = clamp(0.5 - box_distance, 0.0, 1.0);
+    let coverage = shadow_coverage(instance, point);
+
+    // Outset shadows are cut out where the box is and inset shadows are the inverse of the
+    // blurred shape, limited to the box
+    var alpha = coverage * (1.0 - box_coverage);
+    if (instance.inset != 0u) {
+        alpha = (1.0 - coverage) * box_coverage;
+    }
+
+    var result = instance.color;
+    result.w *= alpha * mask_color.w;
+    return result;
+}
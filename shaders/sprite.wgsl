@@ -6,6 +6,10 @@ struct InstancedSprite {
     atlas_top_left: vec2<f32>,
     atlas_size: vec2<f32>,
     color: vec4<f32>,
+    // The columns of the linear part of the transform, applied before the translation
+    transform: vec4<f32>,
+    transform_translation: vec2<f32>,
+    _padding: vec2<f32>,
 }
 
 var<push_constant> constants: ShaderConstants;
@@ -38,7 +42,9 @@ fn vert(
 
     let unit_vertex_pos = UNIT_QUAD_VERTICES[vertex_index];
     let instance = sprites[instance_index];
-    let vertex_pixel_pos = instance.top_left + unit_vertex_pos * instance.size;
+    let matrix = mat2x2<f32>(instance.transform.xy, instance.transform.zw);
+    let vertex_pixel_pos =
+        matrix * (instance.top_left + unit_vertex_pos * instance.size) + instance.transform_translation;
 
     let final_position =
         vec2(0.0, 2.0) + vertex_pixel_pos / constants.surface_size * vec2(1.0, -1.0) * 2.0 - 1.0;
@@ -0,0 +1,86 @@
+#include "common.wgsl"
+
+struct InstancedUndercurl {
+    color: vec4<f32>,
+    start: vec2<f32>,
+    length: f32,
+    thickness: f32,
+    wavelength: f32,
+    amplitude: f32,
+    _padding: vec2<f32>,
+}
+
+var<push_constant> constants: ShaderConstants;
+
+@group(0) @binding(0) var<storage> undercurls: array<InstancedUndercurl>;
+@group(1) @binding(0) var surface: texture_2d<f32>;
+@group(1) @binding(1) var mask: texture_2d<f32>;
+@group(1) @binding(2) var texture_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) instance_index: u32,
+}
+
+@vertex
+fn vert(
+    @builtin(instance_index) instance_index: u32,
+    @builtin(vertex_index) vertex_index: u32,
+) -> VertexOutput {
+    var UNIT_QUAD_VERTICES: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
+        vec2(0.0, 0.0),
+        vec2(1.0, 0.0),
+        vec2(1.0, 1.0),
+        vec2(0.0, 0.0),
+        vec2(1.0, 1.0),
+        vec2(0.0, 1.0)
+    );
+
+    let unit_vertex_pos = UNIT_QUAD_VERTICES[vertex_index];
+    let instance = undercurls[instance_index];
+
+    // The quad isn't extended horizontally so that neighboring undercurls never overlap and
+    // double up their anti aliased ends
+    let half_height = abs(instance.amplitude) + max(instance.thickness / 2.0, 0.5) + 1.0;
+    let top_left = vec2(instance.start.x, instance.start.y - half_height);
+    let size = vec2(instance.length, half_height * 2.0);
+    let vertex_pixel_pos = top_left + unit_vertex_pos * size;
+    let final_position =
+        vec2(0.0, 2.0) + min(vertex_pixel_pos, constants.surface_size) 
+        / constants.surface_size * vec2(1., -1.) * 2.0 - 1.0;
+
+    var out: VertexOutput;
+    out.instance_index = instance_index;
+    out.position = vec4(final_position, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn frag(
+    vertex_output: VertexOutput,
+) -> @location(0) vec4<f32> {
+    let instance = undercurls[vertex_output.instance_index];
+    let mask_color = textureSample(mask, texture_sampler, vertex_output.position.xy / constants.surface_size);
+    let point = vertex_output.position.xy;
+
+    // The phase comes from the absolute x position so adjacent undercurls line up. The vertical
+    // distance to the wave is divided by the length of its gradient to approximate the distance
+    // perpendicular to it, which keeps the thickness even on the slopes.
+    var wave = 0.0;
+    var slope = 0.0;
+    if (instance.wavelength > 0.0) {
+        let frequency = 2.0 * PI / instance.wavelength;
+        wave = instance.amplitude * sin(point.x * frequency);
+        slope = instance.amplitude * frequency * cos(point.x * frequency);
+    }
+    let distance = abs(point.y - instance.start.y - wave) / sqrt(1.0 + slope * slope);
+
+    // Waves thinner than a pixel are drawn a pixel wide and faded instead of breaking up
+    let half_thickness = max(instance.thickness / 2.0, 0.5);
+    let thin_fade = min(instance.thickness, 1.0);
+    let coverage = clamp(half_thickness - distance + 0.5, 0.0, 1.0);
+
+    var result = instance.color;
+    result.w *= coverage * thin_fade * mask_color.w;
+    return result;
+}
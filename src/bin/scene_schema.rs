@@ -0,0 +1,38 @@
+//! Prints the JSON Schema for the scene format. When given scene files, checks each of them
+//! against the schema instead and reports any fields which don't belong.
+
+use std::{env, fs, process::ExitCode};
+
+use vide::{check_scene_schema, migrate_scene, scene_schema};
+
+fn main() -> ExitCode {
+    let paths: Vec<String> = env::args().skip(1).collect();
+    if paths.is_empty() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&scene_schema()).expect("Could not serialize schema")
+        );
+        return ExitCode::SUCCESS;
+    }
+
+    let mut result = ExitCode::SUCCESS;
+    for path in paths {
+        let checked = fs::read(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|error| error.to_string()))
+            .and_then(|mut document: serde_json::Value| {
+                migrate_scene(&mut document)?;
+                check_scene_schema(&document).map_err(|error| error.to_string())
+            });
+
+        match checked {
+            Ok(()) => println!("{}: ok", path),
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                result = ExitCode::FAILURE;
+            }
+        }
+    }
+
+    result
+}
@@ -1,11 +1,19 @@
 mod blur;
+mod ellipse;
 mod glyph;
+mod line_segment;
 mod path;
 mod quad;
+mod shadow;
 mod sprite;
+mod undercurl;
 
 pub use blur::*;
+pub use ellipse::*;
 pub use glyph::*;
+pub use line_segment::*;
 pub use path::*;
 pub use quad::*;
+pub use shadow::*;
 pub use sprite::*;
+pub use undercurl::*;
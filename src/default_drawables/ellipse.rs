@@ -0,0 +1,70 @@
+use glam::*;
+use glamour::Rect;
+use wgpu::*;
+
+use crate::{
+    drawable::Drawable,
+    drawable_reference::{DrawableReference, InstanceBuffer},
+    shader::ShaderConstants,
+    PrimitiveBatch, Renderer, Resources,
+};
+
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Default)]
+#[repr(C, align(16))]
+// An axis aligned ellipse with a fill and a stroke centered on its edge.
+pub struct InstancedEllipse {
+    pub color: Vec4,
+    pub stroke_color: Vec4,
+    pub center: Vec2,
+    pub radii: Vec2,
+    pub stroke_width: f32,
+    pub _padding: Vec3,
+}
+
+pub struct EllipseState {
+    ellipse_buffer: InstanceBuffer<InstancedEllipse>,
+}
+
+impl Drawable for EllipseState {
+    fn new(renderer: &Renderer) -> Self {
+        let ellipse_buffer = InstanceBuffer::new(renderer, "ellipse");
+        Self { ellipse_buffer }
+    }
+
+    fn name(&self) -> &str {
+        "ellipse"
+    }
+
+    fn references(&self) -> Vec<&dyn DrawableReference> {
+        vec![&self.ellipse_buffer]
+    }
+
+    fn start_frame(&mut self) {
+        self.ellipse_buffer.start_frame();
+    }
+
+    fn has_work(&self, batch: &PrimitiveBatch) -> bool {
+        batch.is_ellipses()
+    }
+
+    fn draw<'b, 'a: 'b>(
+        &'a mut self,
+        queue: &Queue,
+        render_pass: &mut RenderPass<'b>,
+        _constants: ShaderConstants,
+        _resources: &Resources,
+        _clip: Option<Rect<u32>>,
+        batch: &PrimitiveBatch,
+    ) {
+        if let Some(ellipses) = batch.as_ellipse_vec() {
+            self.ellipse_buffer.upload(
+                ellipses
+                    .iter()
+                    .map(|ellipse| ellipse.to_instanced())
+                    .collect(),
+                queue,
+            );
+            self.ellipse_buffer.draw(render_pass);
+        }
+    }
+}
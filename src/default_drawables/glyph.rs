@@ -217,10 +217,15 @@ impl Drawable for GlyphState {
             let glyphs: Vec<_> = glyphs
                 .iter()
                 .flat_map(|glyph_run| {
-                    let font = resources.fonts.get(&glyph_run.font_id).unwrap();
-                    let font_ref = font.as_swash_font_ref(glyph_run.font_index).unwrap();
+                    // Runs with missing fonts are skipped. Scene::validate reports them
+                    let Some(font_ref) = resources
+                        .fonts
+                        .get(&glyph_run.font_id)
+                        .and_then(|font| font.as_swash_font_ref(glyph_run.font_index))
+                    else {
+                        return Vec::new();
+                    };
                     self.rasterize_glyph_run(queue, font_ref, glyph_run)
-                        .into_iter()
                 })
                 .collect();
             self.glyph_buffer.upload(glyphs, queue);
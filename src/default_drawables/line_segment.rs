@@ -0,0 +1,71 @@
+use glam::*;
+use glamour::Rect;
+use wgpu::*;
+
+use crate::{
+    drawable::Drawable,
+    drawable_reference::{DrawableReference, InstanceBuffer},
+    shader::ShaderConstants,
+    PrimitiveBatch, Renderer, Resources,
+};
+
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Default)]
+#[repr(C, align(16))]
+// A line segment drawn as a quad oriented along it, extended past the end points for the caps.
+pub struct InstancedLineSegment {
+    pub color: Vec4,
+    pub start: Vec2,
+    pub end: Vec2,
+    pub width: f32,
+    // 0 for butt, 1 for round and 2 for square
+    pub cap: u32,
+    pub _padding: Vec2,
+}
+
+pub struct LineSegmentState {
+    segment_buffer: InstanceBuffer<InstancedLineSegment>,
+}
+
+impl Drawable for LineSegmentState {
+    fn new(renderer: &Renderer) -> Self {
+        let segment_buffer = InstanceBuffer::new(renderer, "line_segment");
+        Self { segment_buffer }
+    }
+
+    fn name(&self) -> &str {
+        "line_segment"
+    }
+
+    fn references(&self) -> Vec<&dyn DrawableReference> {
+        vec![&self.segment_buffer]
+    }
+
+    fn start_frame(&mut self) {
+        self.segment_buffer.start_frame();
+    }
+
+    fn has_work(&self, batch: &PrimitiveBatch) -> bool {
+        batch.is_line_segments()
+    }
+
+    fn draw<'b, 'a: 'b>(
+        &'a mut self,
+        queue: &Queue,
+        render_pass: &mut RenderPass<'b>,
+        _constants: ShaderConstants,
+        _resources: &Resources,
+        _clip: Option<Rect<u32>>,
+        batch: &PrimitiveBatch,
+    ) {
+        if let Some(segments) = batch.as_line_segment_vec() {
+            self.segment_buffer.upload(
+                segments
+                    .iter()
+                    .map(|segment| segment.to_instanced())
+                    .collect(),
+                queue,
+            );
+            self.segment_buffer.draw(render_pass);
+        }
+    }
+}
@@ -4,10 +4,11 @@ use glamour::Rect;
 use lyon::{
     geom::point,
     lyon_tessellation::{
-        BuffersBuilder, FillOptions, FillTessellator, FillVertex, StrokeOptions, StrokeTessellator,
-        StrokeVertex, VertexBuffers,
+        self as tessellation, BuffersBuilder, FillOptions, FillTessellator, FillVertex,
+        StrokeOptions, StrokeTessellator, StrokeVertex, TessellationResult, VertexBuffers,
     },
-    path::Path,
+    math::Point,
+    path::{iterator::PathIterator, path::Builder as PathBuilder, Path, PathEvent},
 };
 use wgpu::*;
 
@@ -15,7 +16,7 @@ use crate::{
     drawable::Drawable,
     drawable_reference::{DrawableReference, GeometryBuffer, GeometryVertex},
     renderer::Renderer,
-    scene::PathCommand,
+    scene::{self, LineCap, LineJoin, PathCommand, StrokeStyle},
     shader::ShaderConstants,
     PrimitiveBatch, Resources,
 };
@@ -78,80 +79,244 @@ impl Drawable for PathState {
                 return;
             }
 
-            let mut geometry: VertexBuffers<PathVertex, u32> = VertexBuffers::new();
-            let mut fill_tesselator = FillTessellator::new();
-            let mut stroke_tesselator = StrokeTessellator::new();
-
-            for scene_path in paths.iter() {
-                let mut builder = Path::builder();
-                builder.begin(point(scene_path.start.x, scene_path.start.y));
-                for path_command in scene_path.commands.iter() {
-                    match path_command {
-                        PathCommand::LineTo { to } => {
-                            builder.line_to(point(to.x, to.y));
-                        }
-                        PathCommand::QuadraticBezierTo { control, to } => {
-                            builder.quadratic_bezier_to(
-                                point(control.x, control.y),
-                                point(to.x, to.y),
-                            );
-                        }
-                        PathCommand::CubicBezierTo {
-                            control1,
-                            control2,
-                            to,
-                        } => {
-                            builder.cubic_bezier_to(
-                                point(control1.x, control1.y),
-                                point(control2.x, control2.y),
-                                point(to.x, to.y),
-                            );
-                        }
-                    }
-                }
+            let geometry = tessellate_paths(paths);
+            self.geometry_buffer
+                .upload(&geometry.vertices, &geometry.indices, queue);
+
+            self.geometry_buffer.draw(render_pass);
+        }
+    }
+}
+
+// Used for paths without a stroke style so they don't need one allocated per frame
+static DEFAULT_STROKE_STYLE: StrokeStyle = StrokeStyle::new();
 
-                builder.end(!scene_path.open);
-                let path = builder.build();
-
-                if let Some(fill) = scene_path.fill {
-                    let fill = Vec4::from_array(fill.into_linear().into());
-                    fill_tesselator
-                        .tessellate_path(
-                            &path,
-                            &FillOptions::default(),
-                            &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex| {
-                                PathVertex {
-                                    color: fill,
-                                    position: vec2(vertex.position().x, vertex.position().y),
-                                    ..Default::default()
-                                }
-                            }),
-                        )
-                        .expect("Could not tesselate path");
+/// Tessellates the fills and strokes of the paths into a single vertex and index buffer in draw
+/// order. Fills and strokes which can't be tessellated, such as those with NaN points, are left
+/// out.
+pub(crate) fn tessellate_paths(paths: &[scene::Path]) -> VertexBuffers<PathVertex, u32> {
+    let mut geometry: VertexBuffers<PathVertex, u32> = VertexBuffers::new();
+    let mut fill_tesselator = FillTessellator::new();
+    let mut stroke_tesselator = StrokeTessellator::new();
+
+    for scene_path in paths {
+        let mut builder = Path::builder();
+        builder.begin(point(scene_path.start.x, scene_path.start.y));
+        for path_command in scene_path.commands.iter() {
+            match path_command {
+                PathCommand::LineTo { to } => {
+                    builder.line_to(point(to.x, to.y));
+                }
+                PathCommand::QuadraticBezierTo { control, to } => {
+                    builder.quadratic_bezier_to(point(control.x, control.y), point(to.x, to.y));
                 }
+                PathCommand::CubicBezierTo {
+                    control1,
+                    control2,
+                    to,
+                } => {
+                    builder.cubic_bezier_to(
+                        point(control1.x, control1.y),
+                        point(control2.x, control2.y),
+                        point(to.x, to.y),
+                    );
+                }
+            }
+        }
 
-                if let Some((width, stroke)) = scene_path.stroke {
-                    let stroke = Vec4::from_array(stroke.into_linear().into());
-                    stroke_tesselator
-                        .tessellate_path(
-                            &path,
-                            &StrokeOptions::default().with_line_width(width),
-                            &mut BuffersBuilder::new(&mut geometry, |vertex: StrokeVertex| {
-                                PathVertex {
-                                    color: stroke,
-                                    position: vec2(vertex.position().x, vertex.position().y),
-                                    ..Default::default()
-                                }
-                            }),
-                        )
-                        .expect("Could not tesselate path");
+        builder.end(!scene_path.open);
+        let path = builder.build();
+
+        if let Some(fill) = scene_path.fill {
+            let fill = Vec4::from_array(fill.into_linear().into());
+            let start = (geometry.vertices.len(), geometry.indices.len());
+            let result = fill_tesselator.tessellate_path(
+                &path,
+                &FillOptions::default(),
+                &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex| PathVertex {
+                    color: fill,
+                    position: vec2(vertex.position().x, vertex.position().y),
+                    ..Default::default()
+                }),
+            );
+            discard_on_error(&mut geometry, start, result);
+        }
+
+        if let Some((width, stroke)) = scene_path.stroke {
+            let stroke = Vec4::from_array(stroke.into_linear().into());
+            let style = scene_path
+                .stroke_style
+                .as_ref()
+                .unwrap_or(&DEFAULT_STROKE_STYLE);
+            let options = stroke_options(width, style);
+            let dashed_path = dash_path(&path, style, options.tolerance);
+            let start = (geometry.vertices.len(), geometry.indices.len());
+            let result = stroke_tesselator.tessellate_path(
+                dashed_path.as_ref().unwrap_or(&path),
+                &options,
+                &mut BuffersBuilder::new(&mut geometry, |vertex: StrokeVertex| PathVertex {
+                    color: stroke,
+                    position: vec2(vertex.position().x, vertex.position().y),
+                    ..Default::default()
+                }),
+            );
+            discard_on_error(&mut geometry, start, result);
+        }
+    }
+
+    geometry
+}
+
+// Lyon may have written part of the geometry before failing, so it is truncated back to where
+// the failed tessellation started
+fn discard_on_error(
+    geometry: &mut VertexBuffers<PathVertex, u32>,
+    (vertex_count, index_count): (usize, usize),
+    result: TessellationResult,
+) {
+    if result.is_err() {
+        geometry.vertices.truncate(vertex_count);
+        geometry.indices.truncate(index_count);
+    }
+}
+
+fn stroke_options(width: f32, style: &StrokeStyle) -> StrokeOptions {
+    let line_cap = |cap| match cap {
+        LineCap::Butt => tessellation::LineCap::Butt,
+        LineCap::Round => tessellation::LineCap::Round,
+        LineCap::Square => tessellation::LineCap::Square,
+    };
+    let line_join = match style.join {
+        LineJoin::Miter => tessellation::LineJoin::Miter,
+        LineJoin::MiterClip => tessellation::LineJoin::MiterClip,
+        LineJoin::Round => tessellation::LineJoin::Round,
+        LineJoin::Bevel => tessellation::LineJoin::Bevel,
+    };
+
+    StrokeOptions::default()
+        .with_line_width(width)
+        .with_start_cap(line_cap(style.start_cap))
+        .with_end_cap(line_cap(style.end_cap))
+        .with_line_join(line_join)
+        // NaN fails the comparison in max and is replaced with the minimum as well
+        .with_miter_limit(style.miter_limit.max(StrokeOptions::MINIMUM_MITER_LIMIT))
+}
+
+/// Splits the path into an open sub path for each dash of the style's dash pattern. Dashes are
+/// measured along the flattened curve so the pattern carries on around corners, and dashes which
+/// cross a corner keep their join. Returns None when the stroke should be drawn solid, which
+/// includes patterns no longer than the tolerance since their dashes couldn't be told apart.
+pub(crate) fn dash_path(path: &Path, style: &StrokeStyle, tolerance: f32) -> Option<Path> {
+    let mut pattern = style.dash_array.clone();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_slice(&style.dash_array);
+    }
+    let pattern_length: f32 = pattern.iter().sum();
+    let valid = pattern
+        .iter()
+        .all(|length| length.is_finite() && *length >= 0.);
+    if !valid || !(pattern_length > tolerance) || !style.dash_offset.is_finite() {
+        return None;
+    }
+
+    let mut dasher = Dasher {
+        pattern: &pattern,
+        builder: Path::builder(),
+        dash: 0,
+        remaining: 0.,
+        drawing: false,
+        splits: 0,
+    };
+    for event in path.iter().flattened(tolerance) {
+        match event {
+            PathEvent::Begin { at } => {
+                // Every sub path starts the pattern over at the offset
+                dasher.begin(at, style.dash_offset.rem_euclid(pattern_length));
+            }
+            PathEvent::Line { from, to } => dasher.line(from, to),
+            PathEvent::End { last, first, close } => {
+                if close {
+                    dasher.line(last, first);
                 }
+                dasher.end();
             }
+            // Flattening leaves only lines
+            PathEvent::Quadratic { .. } | PathEvent::Cubic { .. } => {}
+        }
+    }
 
-            self.geometry_buffer
-                .upload(&geometry.vertices, &geometry.indices, queue);
+    Some(dasher.builder.build())
+}
 
-            self.geometry_buffer.draw(render_pass);
+struct Dasher<'a> {
+    pattern: &'a [f32],
+    builder: PathBuilder,
+    // The index of the current dash or gap in the pattern, dashes being the even ones
+    dash: usize,
+    // How much of the current dash or gap is left
+    remaining: f32,
+    drawing: bool,
+    // How many dashes and gaps the current sub path has been split into
+    splits: usize,
+}
+
+// Keeps long paths with fine patterns from producing millions of dashes, and ends the loop in
+// Dasher::line once the distance travelled is too large for a dash to add to it
+const MAX_DASH_SPLITS: usize = 10_000;
+
+impl Dasher<'_> {
+    fn begin(&mut self, at: Point, mut offset: f32) {
+        self.dash = 0;
+        self.splits = 0;
+        // A dash which ends exactly at the offset is still started, so zero length first dashes
+        // aren't skipped
+        while offset > self.pattern[self.dash] {
+            offset -= self.pattern[self.dash];
+            self.dash = (self.dash + 1) % self.pattern.len();
+        }
+        self.remaining = self.pattern[self.dash] - offset;
+        self.drawing = self.dash % 2 == 0;
+        if self.drawing {
+            self.builder.begin(at);
+        }
+    }
+
+    fn line(&mut self, from: Point, to: Point) {
+        let length = (to - from).length();
+        let mut travelled = 0.;
+        while length - travelled > self.remaining {
+            travelled += self.remaining;
+            let point = from.lerp(to, travelled / length);
+            if self.drawing {
+                self.builder.line_to(point);
+                self.builder.end(false);
+            } else {
+                self.builder.begin(point);
+            }
+            self.drawing = !self.drawing;
+            self.dash = (self.dash + 1) % self.pattern.len();
+            self.remaining = self.pattern[self.dash];
+
+            // The rest of the sub path is drawn solid once it has been split too often
+            self.splits += 1;
+            if self.splits == MAX_DASH_SPLITS {
+                if !self.drawing {
+                    self.builder.begin(point);
+                    self.drawing = true;
+                }
+                self.remaining = f32::INFINITY;
+            }
+        }
+        self.remaining -= length - travelled;
+        if self.drawing {
+            self.builder.line_to(to);
+        }
+    }
+
+    fn end(&mut self) {
+        if self.drawing {
+            self.builder.end(false);
+            self.drawing = false;
         }
     }
 }
@@ -10,16 +10,38 @@ use crate::{
 };
 
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Default)]
-#[repr(C, align(64))]
-// An axis aligned quad supporting positioning, scaling, corner radius, and optionally an internal blur with
-// the previous layer or an external blur for use with shadows.
+#[repr(C, align(16))]
+// A quad supporting positioning, scaling, affine transforms, per corner radii, per side borders,
+// gradient fills, and optionally an internal blur with the previous layer or an external blur for
+// use with shadows.
 pub struct InstancedQuad {
     pub color: Vec4,
-    pub _padding: Vec4,
+    // Top left, top right, bottom right, bottom left
+    pub corner_radii: Vec4,
+    // Top, right, bottom, left
+    pub border_widths: Vec4,
+    // Top, right, bottom, left
+    pub border_colors: [Vec4; 4],
+    // Dash length, gap length, phase, and cap with 0 for butt, 1 for round and 2 for square. A
+    // zero gap draws a solid border
+    pub border_dash: Vec4,
+    // Linear gradients store the start and end of the gradient line, radial gradients the center
+    // and radius
+    pub gradient_parameters: Vec4,
+    pub gradient_offsets: Vec4,
+    // Srgb encoded with 8 bits per channel
+    pub gradient_colors: UVec4,
+    // The columns of the linear part of the transform, applied before the translation
+    pub transform: Vec4,
+    pub transform_translation: Vec2,
+    pub _padding: Vec2,
     pub top_left: Vec2,
     pub size: Vec2,
-    pub __padding: Vec2,
-    pub corner_radius: f32,
+    // 0 for a solid fill, 1 for linear and 2 for radial
+    pub gradient_kind: u32,
+    // 0 for linear rgb, 1 for oklab
+    pub gradient_interpolation: u32,
+    pub gradient_stop_count: u32,
     pub edge_blur: f32,
 }
 
@@ -60,7 +82,10 @@ impl Drawable for QuadState {
     ) {
         if let Some(quads) = batch.as_quad_vec() {
             self.quad_buffer.upload(
-                quads.iter().map(|quad| quad.to_instanced()).collect(),
+                quads
+                    .iter()
+                    .filter_map(|quad| quad.to_instanced())
+                    .collect(),
                 queue,
             );
             self.quad_buffer.draw(render_pass);
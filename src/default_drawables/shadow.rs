@@ -0,0 +1,75 @@
+use glam::*;
+use glamour::Rect;
+use wgpu::*;
+
+use crate::{
+    drawable::Drawable,
+    drawable_reference::{DrawableReference, InstanceBuffer},
+    shader::ShaderConstants,
+    PrimitiveBatch, Renderer, Resources,
+};
+
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Default)]
+#[repr(C, align(16))]
+// A gaussian blurred rounded rect shadow, drawn either outside or inside of the box casting it.
+pub struct InstancedShadow {
+    pub color: Vec4,
+    // Top left, top right, bottom right, bottom left
+    pub box_corner_radii: Vec4,
+    pub shadow_corner_radii: Vec4,
+    pub box_top_left: Vec2,
+    pub box_size: Vec2,
+    // The box after applying the offset and spread
+    pub shadow_top_left: Vec2,
+    pub shadow_size: Vec2,
+    // The standard deviation of the blur
+    pub sigma: f32,
+    // 1 for inset shadows, 0 for outset
+    pub inset: u32,
+    pub _padding: Vec2,
+}
+
+pub struct ShadowState {
+    shadow_buffer: InstanceBuffer<InstancedShadow>,
+}
+
+impl Drawable for ShadowState {
+    fn new(renderer: &Renderer) -> Self {
+        let shadow_buffer = InstanceBuffer::new(renderer, "shadow");
+        Self { shadow_buffer }
+    }
+
+    fn name(&self) -> &str {
+        "shadow"
+    }
+
+    fn references(&self) -> Vec<&dyn DrawableReference> {
+        vec![&self.shadow_buffer]
+    }
+
+    fn start_frame(&mut self) {
+        self.shadow_buffer.start_frame();
+    }
+
+    fn has_work(&self, batch: &PrimitiveBatch) -> bool {
+        batch.is_shadows()
+    }
+
+    fn draw<'b, 'a: 'b>(
+        &'a mut self,
+        queue: &Queue,
+        render_pass: &mut RenderPass<'b>,
+        _constants: ShaderConstants,
+        _resources: &Resources,
+        _clip: Option<Rect<u32>>,
+        batch: &PrimitiveBatch,
+    ) {
+        if let Some(shadows) = batch.as_shadow_vec() {
+            self.shadow_buffer.upload(
+                shadows.iter().map(|shadow| shadow.to_instanced()).collect(),
+                queue,
+            );
+            self.shadow_buffer.draw(render_pass);
+        }
+    }
+}
@@ -21,6 +21,10 @@ pub struct InstancedSprite {
     pub atlas_top_left: Vec2,
     pub atlas_size: Vec2,
     pub color: Vec4,
+    // The columns of the linear part of the transform, applied before the translation
+    pub transform: Vec4,
+    pub transform_translation: Vec2,
+    pub _padding: Vec2,
 }
 
 pub struct SpriteState {
@@ -34,7 +38,7 @@ impl SpriteState {
         resources: &Resources,
         queue: &Queue,
         sprite: &Sprite<TextureId>,
-    ) -> InstancedSprite {
+    ) -> Option<InstancedSprite> {
         let Some((_, sprite_location)) = self.atlas.lookup_or_upload(queue, sprite.texture, || {
             let Some(texture) = resources.textures.get(&sprite.texture) else {
                 warn!("Sprite texture not in resources");
@@ -43,16 +47,19 @@ impl SpriteState {
 
             ConstructResult::Constructed((), texture.data.clone(), texture.size)
         }) else {
-            panic!("Referenced texture not in scene resources");
+            return None;
         };
 
-        InstancedSprite {
+        Some(InstancedSprite {
             top_left: *sprite.top_left.as_raw(),
             size: *sprite.size.as_raw(),
             atlas_top_left: sprite_location.min.as_raw().as_vec2(),
             atlas_size: sprite_location.size().as_raw().as_vec2(),
             color: Vec4::from_array(sprite.color.into_linear().into()),
-        }
+            transform: sprite.transform.unwrap_or_default().matrix_columns(),
+            transform_translation: sprite.transform.unwrap_or_default().translation_vector(),
+            ..Default::default()
+        })
     }
 }
 
@@ -95,7 +102,7 @@ impl Drawable for SpriteState {
         if let Some(sprites) = batch.as_sprite_vec() {
             let sprites: Vec<_> = sprites
                 .iter()
-                .map(|sprite| self.upload_sprite(resources, queue, sprite))
+                .filter_map(|sprite| self.upload_sprite(resources, queue, sprite))
                 .collect();
 
             self.sprite_buffer.upload(sprites, queue);
@@ -0,0 +1,71 @@
+use glam::*;
+use glamour::Rect;
+use wgpu::*;
+
+use crate::{
+    drawable::Drawable,
+    drawable_reference::{DrawableReference, InstanceBuffer},
+    shader::ShaderConstants,
+    PrimitiveBatch, Renderer, Resources,
+};
+
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Default)]
+#[repr(C, align(16))]
+// A horizontal sine wave covering the cells from start to start + length.
+pub struct InstancedUndercurl {
+    pub color: Vec4,
+    pub start: Vec2,
+    pub length: f32,
+    pub thickness: f32,
+    pub wavelength: f32,
+    pub amplitude: f32,
+    pub _padding: Vec2,
+}
+
+pub struct UndercurlState {
+    undercurl_buffer: InstanceBuffer<InstancedUndercurl>,
+}
+
+impl Drawable for UndercurlState {
+    fn new(renderer: &Renderer) -> Self {
+        let undercurl_buffer = InstanceBuffer::new(renderer, "undercurl");
+        Self { undercurl_buffer }
+    }
+
+    fn name(&self) -> &str {
+        "undercurl"
+    }
+
+    fn references(&self) -> Vec<&dyn DrawableReference> {
+        vec![&self.undercurl_buffer]
+    }
+
+    fn start_frame(&mut self) {
+        self.undercurl_buffer.start_frame();
+    }
+
+    fn has_work(&self, batch: &PrimitiveBatch) -> bool {
+        batch.is_undercurls()
+    }
+
+    fn draw<'b, 'a: 'b>(
+        &'a mut self,
+        queue: &Queue,
+        render_pass: &mut RenderPass<'b>,
+        _constants: ShaderConstants,
+        _resources: &Resources,
+        _clip: Option<Rect<u32>>,
+        batch: &PrimitiveBatch,
+    ) {
+        if let Some(undercurls) = batch.as_undercurl_vec() {
+            self.undercurl_buffer.upload(
+                undercurls
+                    .iter()
+                    .map(|undercurl| undercurl.to_instanced())
+                    .collect(),
+                queue,
+            );
+            self.undercurl_buffer.draw(render_pass);
+        }
+    }
+}
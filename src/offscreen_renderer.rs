@@ -54,6 +54,17 @@ impl OffscreenRenderer {
         self
     }
 
+    /// Skips primitives which fail `Scene::validate` when drawing. See
+    /// `Renderer::validate_scenes`.
+    pub fn set_scene_validation(&mut self, validate: bool) {
+        self.renderer.validate_scenes = validate;
+    }
+
+    pub fn with_scene_validation(mut self, validate: bool) -> Self {
+        self.set_scene_validation(validate);
+        self
+    }
+
     fn instance_backends() -> Backends {
         #[cfg(target_os = "macos")]
         {
@@ -3,20 +3,24 @@ use std::collections::HashMap;
 use futures::executor::block_on;
 use glam::*;
 use glamour::{AsRaw, Rect};
+use log::warn;
 use wgpu::*;
 
 #[cfg(not(target_os = "macos"))]
 use wgpu_profiler::{GpuProfiler, GpuProfilerSettings};
 
 use crate::{
-    default_drawables::{BlurState, GlyphState, PathState, QuadState, SpriteState},
+    default_drawables::{
+        BlurState, EllipseState, GlyphState, LineSegmentState, PathState, QuadState, ShadowState,
+        SpriteState, UndercurlState,
+    },
     drawable::Drawable,
     drawable_pipeline::{
         DrawableContext, DrawablePipeline, RenderContentParams, RenderDrawableParams,
     },
     drawable_reference::ATLAS_SIZE,
     shader::{ShaderConstants, ShaderLoader},
-    LayerContents, Resources, Scene,
+    LayerContents, Resources, Scene, SceneProblem,
 };
 
 pub struct DrawContext<'a> {
@@ -49,6 +53,13 @@ pub struct Renderer {
     pub universal_mask_bind_group: BindGroup,
     drawables: Vec<DrawablePipeline>,
 
+    /// Whether scenes are checked with `Scene::validate` before drawing so that invalid
+    /// primitives are skipped instead of panicking or drawing garbage. Off by default since it
+    /// looks up the font of every glyph run each frame.
+    pub validate_scenes: bool,
+    // Problems are only logged when they change so a broken scene doesn't flood the log
+    last_problems: Vec<SceneProblem>,
+
     pub(crate) shader_loader: ShaderLoader,
 }
 
@@ -193,6 +204,9 @@ impl Renderer {
 
             drawables: Vec::new(),
 
+            validate_scenes: false,
+            last_problems: Vec::new(),
+
             shader_loader,
         }
     }
@@ -218,10 +232,14 @@ impl Renderer {
 
     pub async fn add_default_drawables(&mut self) {
         self.add_drawable::<BlurState>().await;
+        self.add_drawable::<EllipseState>().await;
         self.add_drawable::<GlyphState>().await;
+        self.add_drawable::<LineSegmentState>().await;
         self.add_drawable::<PathState>().await;
         self.add_drawable::<QuadState>().await;
+        self.add_drawable::<ShadowState>().await;
         self.add_drawable::<SpriteState>().await;
+        self.add_drawable::<UndercurlState>().await;
     }
 
     pub async fn with_default_drawables(mut self) -> Self {
@@ -329,6 +347,27 @@ impl Renderer {
             }
         }
 
+        // Primitives which would panic or send garbage to the gpu are dropped from the frame
+        let problems = if self.validate_scenes {
+            profiling::scope!("Validate Scene");
+            scene.validate()
+        } else {
+            Vec::new()
+        };
+        if problems != self.last_problems {
+            for problem in &problems {
+                warn!("Skipping invalid primitive. {}", problem);
+            }
+            self.last_problems.clone_from(&problems);
+        }
+        let valid_layers;
+        let layers = if problems.is_empty() {
+            &scene.layers
+        } else {
+            valid_layers = scene.layers_without_problems(&problems);
+            &valid_layers
+        };
+
         let frame_view = frame.create_view(&Default::default());
 
         let constants = ShaderConstants {
@@ -346,7 +385,7 @@ impl Renderer {
                 label: Some("Render Encoder"),
             });
         let mut first = true;
-        for layer in scene.layers.iter() {
+        for layer in layers.iter() {
             profiling::scope!("Layer");
 
             self.draw_mask(
@@ -1,9 +1,27 @@
+mod binary;
 mod blur;
+mod diff;
+mod editor;
+mod ellipse;
+mod format;
 mod glyph_run;
+mod gradient;
 mod layer;
+mod line_segment;
+mod load;
 mod path;
 mod quad;
+mod recording;
+mod resource_reference;
+mod schema;
+mod shadow;
 mod sprite;
+mod stats;
+mod svg;
+mod svg_import;
+mod transform;
+mod undercurl;
+mod validate;
 
 use std::collections::HashMap;
 
@@ -12,14 +30,31 @@ use palette::Srgba;
 use parley::Layout;
 use serde::{Deserialize, Serialize};
 
+pub use binary::*;
 pub use blur::*;
+pub use diff::*;
+pub use editor::*;
+pub use ellipse::*;
+pub use format::*;
 pub use glyph_run::*;
+pub use gradient::*;
 pub use layer::*;
+pub use line_segment::*;
+pub use load::*;
 pub use path::*;
 pub use quad::*;
+pub use recording::*;
+pub use resource_reference::*;
+pub use schema::*;
+pub use shadow::*;
 pub use sprite::*;
+pub use stats::*;
+pub use svg_import::*;
+pub use transform::*;
+pub use undercurl::*;
+pub use validate::*;
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default)]
 pub struct Scene {
     pub layers: Vec<Layer>,
     pub resources: Resources,
@@ -88,6 +123,42 @@ impl Scene {
         self
     }
 
+    pub fn add_shadow(&mut self, shadow: Shadow) {
+        self.layer_mut().add_shadow(shadow);
+    }
+
+    pub fn with_shadow(mut self, shadow: Shadow) -> Self {
+        self.add_shadow(shadow);
+        self
+    }
+
+    pub fn add_ellipse(&mut self, ellipse: Ellipse) {
+        self.layer_mut().add_ellipse(ellipse);
+    }
+
+    pub fn with_ellipse(mut self, ellipse: Ellipse) -> Self {
+        self.add_ellipse(ellipse);
+        self
+    }
+
+    pub fn add_line_segment(&mut self, line_segment: LineSegment) {
+        self.layer_mut().add_line_segment(line_segment);
+    }
+
+    pub fn with_line_segment(mut self, line_segment: LineSegment) -> Self {
+        self.add_line_segment(line_segment);
+        self
+    }
+
+    pub fn add_undercurl(&mut self, undercurl: Undercurl) {
+        self.layer_mut().add_undercurl(undercurl);
+    }
+
+    pub fn with_undercurl(mut self, undercurl: Undercurl) -> Self {
+        self.add_undercurl(undercurl);
+        self
+    }
+
     pub fn add_path(&mut self, path: Path) {
         self.layer_mut().add_path(path);
     }
@@ -97,25 +168,21 @@ impl Scene {
         self
     }
 
-    /// Adds the given sprite with included texture to the current layer.
+    /// Adds the given sprite with included texture to the current layer. The texture is stored in
+    /// the resources, reusing the existing entry if an identical texture was stored before.
     ///
-    /// WARNING: This will store whatever texture is associated with this sprite in the resources
-    /// without checking for duplication. If you want to avoid duplication, you should use
-    /// `store_texture` on `self.resources` to get a TextureId, and then use that TextureId in the
-    /// sprites. To add a Sprite<TextureId>, use `add_sprite` on the layer like this:
-    /// `scene.layer_mut().add_sprite(sprite)`.
+    /// To add a Sprite<TextureId> from a texture already in the resources, use `add_sprite` on
+    /// the layer like this: `scene.layer_mut().add_sprite(sprite)`.
     pub fn add_sprite(&mut self, sprite: Sprite<Texture>) {
         let sprite = sprite.redirect_texture(&mut self.resources);
         self.layer_mut().add_sprite(sprite);
     }
 
-    /// Adds the given sprite with included texture to the current layer.
+    /// Adds the given sprite with included texture to the current layer. The texture is stored in
+    /// the resources, reusing the existing entry if an identical texture was stored before.
     ///
-    /// WARNING: This will store whatever texture is associated with this sprite in the resources
-    /// without checking for duplication. If you want to avoid duplication, you should use
-    /// `store_texture` on `self.resources` to get a TextureId, and then use that TextureId in the
-    /// sprites. To add a Sprite<TextureId>, use `add_sprite` on the layer like this:
-    /// `scene.layer_mut().add_sprite(sprite)`.
+    /// To add a Sprite<TextureId> from a texture already in the resources, use `add_sprite` on
+    /// the layer like this: `scene.layer_mut().add_sprite(sprite)`.
     pub fn with_sprite(mut self, sprite: Sprite<Texture>) -> Self {
         self.add_sprite(sprite);
         self
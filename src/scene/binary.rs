@@ -0,0 +1,508 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    sync::Arc,
+};
+
+use glamour::Size2;
+use serde_json::{Map, Value};
+
+use super::{
+    migrate_scene, Font, FontId, Layer, Resources, Scene, Texture, TextureId, SCENE_FORMAT_VERSION,
+};
+
+/// Magic bytes at the start of every binary scene. Used to tell binary scenes apart from json
+/// scenes.
+pub const BINARY_SCENE_MAGIC: &[u8; 8] = b"VIDESCN\0";
+
+// Value tags
+const NULL: u8 = 0;
+const FALSE: u8 = 1;
+const TRUE: u8 = 2;
+const UNSIGNED: u8 = 3;
+const SIGNED: u8 = 4;
+const FLOAT32: u8 = 5;
+const FLOAT64: u8 = 6;
+const STRING: u8 = 7;
+const ARRAY: u8 = 8;
+const OBJECT: u8 = 9;
+const TABLE: u8 = 10;
+
+// Column tags used for the columns of a table
+const VALUE_COLUMN: u8 = 0;
+const UNSIGNED_COLUMN: u8 = 1;
+const SIGNED_COLUMN: u8 = 2;
+const FLOAT32_COLUMN: u8 = 3;
+const TABLE_COLUMN: u8 = 4;
+
+impl Scene {
+    /// Writes the scene in the compact binary format. Fonts and textures are stored as raw bytes
+    /// instead of base64, and arrays of identically shaped objects such as the glyphs in a glyph
+    /// run are packed column by column. The layers otherwise have the same structure as in the
+    /// json format, so the same migrations apply to both.
+    pub fn write_binary(&self, mut writer: impl Write) -> io::Result<()> {
+        let mut encoder = BinaryEncoder::default();
+        encoder.write_header(BINARY_SCENE_MAGIC);
+        encoder.write_resources(&self.resources);
+        encoder.write_value(&serde_json::to_value(&self.layers).map_err(invalid_data)?);
+        writer.write_all(&encoder.bytes)
+    }
+
+    /// Reads a scene written with `write_binary`, migrating it to the current format version if
+    /// it was written by an older version of vide.
+    pub fn read_binary(mut reader: impl Read) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        let mut decoder = BinaryDecoder::new(&bytes);
+        let format_version = decoder.read_header(BINARY_SCENE_MAGIC)?;
+        let resources = decoder.read_resources()?;
+        let layers = decoder.read_value()?;
+        if !decoder.is_finished() {
+            return Err(invalid_data("Trailing bytes after binary scene"));
+        }
+
+        Ok(Self {
+            layers: decode_layers(layers, format_version)?,
+            resources,
+        })
+    }
+}
+
+/// Migrates layers written with the given format version and deserializes them.
+pub(crate) fn decode_layers(layers: Value, format_version: u32) -> io::Result<Vec<Layer>> {
+    let mut document = Value::Object(Map::from_iter([
+        ("format_version".to_string(), format_version.into()),
+        ("layers".to_string(), layers),
+    ]));
+    migrate_scene(&mut document).map_err(invalid_data)?;
+    serde_json::from_value(document["layers"].take()).map_err(invalid_data)
+}
+
+/// Returns true if the given bytes start with the binary scene magic.
+pub fn is_binary_scene(bytes: &[u8]) -> bool {
+    bytes.starts_with(BINARY_SCENE_MAGIC)
+}
+
+pub(crate) fn invalid_data(error: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+#[derive(Default)]
+pub(crate) struct BinaryEncoder {
+    pub bytes: Vec<u8>,
+    strings: HashMap<String, u64>,
+}
+
+impl BinaryEncoder {
+    pub fn write_header(&mut self, magic: &[u8; 8]) {
+        self.bytes.extend_from_slice(magic);
+        self.write_u32(SCENE_FORMAT_VERSION);
+    }
+
+    pub fn write_resources(&mut self, resources: &Resources) {
+        self.write_fonts(resources.fonts.iter());
+        self.write_textures(resources.textures.iter());
+    }
+
+    pub fn write_fonts<'a>(
+        &mut self,
+        fonts: impl ExactSizeIterator<Item = (&'a FontId, &'a Font)>,
+    ) {
+        self.write_length(fonts.len());
+        for (id, font) in fonts {
+            self.write_u64(id.raw());
+            self.write_bytes(&font.data);
+        }
+    }
+
+    pub fn write_textures<'a>(
+        &mut self,
+        textures: impl ExactSizeIterator<Item = (&'a TextureId, &'a Texture)>,
+    ) {
+        self.write_length(textures.len());
+        for (id, texture) in textures {
+            self.write_u64(id.raw());
+            self.write_u32(texture.size.width);
+            self.write_u32(texture.size.height);
+            self.write_bytes(&texture.data);
+        }
+    }
+
+    pub fn write_value(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.bytes.push(NULL),
+            Value::Bool(false) => self.bytes.push(FALSE),
+            Value::Bool(true) => self.bytes.push(TRUE),
+            Value::Number(_) => {
+                if let Some(value) = value.as_u64() {
+                    self.bytes.push(UNSIGNED);
+                    self.write_varint(value);
+                } else if let Some(value) = value.as_i64() {
+                    self.bytes.push(SIGNED);
+                    self.write_signed(value);
+                } else if let Some(value) = as_exact_f32(value) {
+                    self.bytes.push(FLOAT32);
+                    self.bytes.extend_from_slice(&value.to_le_bytes());
+                } else {
+                    self.bytes.push(FLOAT64);
+                    let value = value.as_f64().unwrap_or_default();
+                    self.bytes.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            Value::String(string) => {
+                self.bytes.push(STRING);
+                self.write_string(string);
+            }
+            Value::Array(values) => {
+                let values: Vec<&Value> = values.iter().collect();
+                if let Some(rows) = as_table(&values) {
+                    self.bytes.push(TABLE);
+                    self.write_length(rows.len());
+                    self.write_table(&rows);
+                } else {
+                    self.bytes.push(ARRAY);
+                    self.write_length(values.len());
+                    for value in values {
+                        self.write_value(value);
+                    }
+                }
+            }
+            Value::Object(object) => {
+                self.bytes.push(OBJECT);
+                self.write_length(object.len());
+                for (key, value) in object {
+                    self.write_string(key);
+                    self.write_value(value);
+                }
+            }
+        }
+    }
+
+    // Tables store the keys once and then each column of values one after the other. Columns
+    // made up entirely of integers or floats are written without any per value tags, and columns
+    // of objects are split into nested tables, so an array of glyphs ends up as a column of ids
+    // followed by a column of x offsets and a column of y offsets.
+    fn write_table(&mut self, rows: &[&Map<String, Value>]) {
+        self.write_length(rows[0].len());
+        for key in rows[0].keys() {
+            self.write_string(key);
+            let column: Vec<&Value> = rows.iter().map(|row| &row[key]).collect();
+            self.write_column(&column);
+        }
+    }
+
+    fn write_column(&mut self, column: &[&Value]) {
+        if column.iter().all(|value| value.is_u64()) {
+            self.bytes.push(UNSIGNED_COLUMN);
+            for value in column {
+                self.write_varint(value.as_u64().unwrap());
+            }
+        } else if column.iter().all(|value| value.is_i64() || value.is_u64()) {
+            // u64 values which don't fit in an i64 fall through to the value column
+            if let Some(values) = column
+                .iter()
+                .map(|value| value.as_i64())
+                .collect::<Option<Vec<_>>>()
+            {
+                self.bytes.push(SIGNED_COLUMN);
+                for value in values {
+                    self.write_signed(value);
+                }
+            } else {
+                self.write_value_column(column);
+            }
+        } else if let Some(values) = column
+            .iter()
+            .map(|value| as_exact_f32(value))
+            .collect::<Option<Vec<_>>>()
+        {
+            self.bytes.push(FLOAT32_COLUMN);
+            for value in values {
+                self.bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        } else if let Some(rows) = as_table(column) {
+            self.bytes.push(TABLE_COLUMN);
+            self.write_table(&rows);
+        } else {
+            self.write_value_column(column);
+        }
+    }
+
+    fn write_value_column(&mut self, column: &[&Value]) {
+        self.bytes.push(VALUE_COLUMN);
+        for value in column {
+            self.write_value(value);
+        }
+    }
+
+    // Strings are interned. The first occurrence of a string is written as a zero followed by the
+    // string itself, and later occurrences as one plus the index of the first occurrence.
+    fn write_string(&mut self, string: &str) {
+        if let Some(index) = self.strings.get(string) {
+            self.write_varint(index + 1);
+        } else {
+            self.strings
+                .insert(string.to_string(), self.strings.len() as u64);
+            self.write_varint(0);
+            self.write_bytes(string.as_bytes());
+        }
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_length(bytes.len());
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn write_length(&mut self, length: usize) {
+        self.write_varint(length as u64);
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_signed(&mut self, value: i64) {
+        self.write_varint(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    fn write_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+}
+
+/// Returns the value as an f32 if it is a float which can be stored as one without losing
+/// precision.
+fn as_exact_f32(value: &Value) -> Option<f32> {
+    let value = value.as_f64().filter(|_| value.is_f64())?;
+    (value as f32 as f64 == value).then_some(value as f32)
+}
+
+/// Returns the values as rows of a table if there are at least two of them and they are all
+/// objects with the same non empty set of keys.
+fn as_table<'a>(values: &[&'a Value]) -> Option<Vec<&'a Map<String, Value>>> {
+    let rows = values
+        .iter()
+        .map(|value| value.as_object())
+        .collect::<Option<Vec<_>>>()?;
+    let first = rows.first()?;
+    let is_table = rows.len() > 1
+        && !first.is_empty()
+        && rows
+            .iter()
+            .all(|row| row.len() == first.len() && row.keys().eq(first.keys()));
+
+    is_table.then_some(rows)
+}
+
+pub(crate) struct BinaryDecoder<'a> {
+    bytes: &'a [u8],
+    strings: Vec<String>,
+}
+
+impl<'a> BinaryDecoder<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            strings: Vec::new(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Reads the magic and returns the format version the scene was written with.
+    pub fn read_header(&mut self, magic: &[u8; 8]) -> io::Result<u32> {
+        if self.take(magic.len())? != magic {
+            return Err(invalid_data("Missing magic bytes"));
+        }
+
+        let format_version = self.read_u32()?;
+        if format_version > SCENE_FORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "Scene format version {} is newer than the supported version {}",
+                format_version, SCENE_FORMAT_VERSION
+            )));
+        }
+
+        Ok(format_version)
+    }
+
+    pub fn read_resources(&mut self) -> io::Result<Resources> {
+        let mut resources = Resources::default();
+        self.read_fonts(&mut resources)?;
+        self.read_textures(&mut resources)?;
+        Ok(resources)
+    }
+
+    pub fn read_fonts(&mut self, resources: &mut Resources) -> io::Result<()> {
+        for _ in 0..self.read_length()? {
+            let id = FontId::new(self.read_u64()?);
+            let data = self.read_bytes()?.to_vec();
+            resources.fonts.insert(
+                id,
+                Font {
+                    data: Arc::new(data),
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn read_textures(&mut self, resources: &mut Resources) -> io::Result<()> {
+        for _ in 0..self.read_length()? {
+            let id = TextureId::new(self.read_u64()?);
+            let size = Size2::new(self.read_u32()?, self.read_u32()?);
+            let data = self.read_bytes()?.to_vec();
+            resources.textures.insert(id, Texture { data, size });
+        }
+
+        Ok(())
+    }
+
+    pub fn read_value(&mut self) -> io::Result<Value> {
+        Ok(match self.read_u8()? {
+            NULL => Value::Null,
+            FALSE => Value::Bool(false),
+            TRUE => Value::Bool(true),
+            UNSIGNED => self.read_varint()?.into(),
+            SIGNED => self.read_signed()?.into(),
+            FLOAT32 => self.read_f32()?.into(),
+            FLOAT64 => f64::from_le_bytes(self.take(8)?.try_into().unwrap()).into(),
+            STRING => Value::String(self.read_string()?),
+            ARRAY => {
+                let length = self.read_length()?;
+                let values = (0..length)
+                    .map(|_| self.read_value())
+                    .collect::<io::Result<Vec<_>>>()?;
+                Value::Array(values)
+            }
+            OBJECT => {
+                let mut object = Map::new();
+                for _ in 0..self.read_length()? {
+                    let key = self.read_string()?;
+                    object.insert(key, self.read_value()?);
+                }
+                Value::Object(object)
+            }
+            TABLE => {
+                let rows = self.read_length()?;
+                Value::Array(self.read_table(rows)?)
+            }
+            tag => return Err(invalid_data(format!("Unknown value tag {}", tag))),
+        })
+    }
+
+    fn read_table(&mut self, rows: usize) -> io::Result<Vec<Value>> {
+        let mut objects = vec![Map::new(); rows];
+        for _ in 0..self.read_length()? {
+            let key = self.read_string()?;
+            let column = self.read_column(rows)?;
+            for (object, value) in objects.iter_mut().zip(column) {
+                object.insert(key.clone(), value);
+            }
+        }
+
+        Ok(objects.into_iter().map(Value::Object).collect())
+    }
+
+    fn read_column(&mut self, rows: usize) -> io::Result<Vec<Value>> {
+        match self.read_u8()? {
+            VALUE_COLUMN => (0..rows).map(|_| self.read_value()).collect(),
+            UNSIGNED_COLUMN => (0..rows).map(|_| Ok(self.read_varint()?.into())).collect(),
+            SIGNED_COLUMN => (0..rows).map(|_| Ok(self.read_signed()?.into())).collect(),
+            FLOAT32_COLUMN => (0..rows).map(|_| Ok(self.read_f32()?.into())).collect(),
+            TABLE_COLUMN => self.read_table(rows),
+            tag => Err(invalid_data(format!("Unknown column tag {}", tag))),
+        }
+    }
+
+    fn read_string(&mut self) -> io::Result<String> {
+        match self.read_varint()? {
+            0 => {
+                let string = std::str::from_utf8(self.read_bytes()?)
+                    .map_err(invalid_data)?
+                    .to_string();
+                self.strings.push(string.clone());
+                Ok(string)
+            }
+            index => self
+                .strings
+                .get(index as usize - 1)
+                .cloned()
+                .ok_or_else(|| invalid_data(format!("Unknown string index {}", index - 1))),
+        }
+    }
+
+    pub fn read_bytes(&mut self) -> io::Result<&'a [u8]> {
+        let length = self.read_length()?;
+        self.take(length)
+    }
+
+    // Every element of an array, table row or string takes up at least one byte, so lengths
+    // larger than the remaining input are rejected before anything is allocated for them.
+    pub fn read_length(&mut self) -> io::Result<usize> {
+        let length = self.read_varint()?;
+        if length > self.bytes.len() as u64 {
+            return Err(invalid_data(format!("Length {} exceeds the input", length)));
+        }
+        Ok(length as usize)
+    }
+
+    pub fn read_u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn read_u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn read_u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn read_f32(&mut self) -> io::Result<f32> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_signed(&mut self) -> io::Result<i64> {
+        let value = self.read_varint()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    fn read_varint(&mut self) -> io::Result<u64> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(invalid_data("Varint is too long"))
+    }
+
+    pub fn take(&mut self, length: usize) -> io::Result<&'a [u8]> {
+        if length > self.bytes.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Unexpected end of binary scene",
+            ));
+        }
+
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(taken)
+    }
+}
@@ -4,7 +4,7 @@ use glamour::{AsRaw, Point2, Size2};
 use palette::Srgba;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Blur {
     pub top_left: Point2,
     pub size: Size2,
@@ -0,0 +1,335 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    hash::Hash,
+};
+
+use glamour::Rect;
+
+use super::{FontId, Layer, LayerContents, PrimitiveBatch, PrimitiveKind, Scene, TextureId};
+
+/// The differences between two scenes as reported by `Scene::diff`. Layers and batches are
+/// compared by index, so inserting a layer in the middle of a scene shows up as every following
+/// layer being modified and one layer being added at the end.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SceneDiff {
+    pub layers: Vec<LayerDiff>,
+    pub resources: ResourcesDiff,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LayerDiff {
+    Added {
+        index: usize,
+    },
+    Removed {
+        index: usize,
+    },
+    Modified {
+        index: usize,
+        clip: Option<ClipChange>,
+        mask: Option<MaskChange>,
+        batches: Vec<BatchDiff>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipChange {
+    pub old: Option<Rect<u32>>,
+    pub new: Option<Rect<u32>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MaskChange {
+    Added,
+    Removed,
+    Modified(Vec<BatchDiff>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BatchDiff {
+    Added {
+        index: usize,
+        kind: PrimitiveKind,
+        len: usize,
+    },
+    Removed {
+        index: usize,
+        kind: PrimitiveKind,
+        len: usize,
+    },
+    /// A batch at the same index changed. If the batches are of the same kind,
+    /// `changed_primitives` counts the primitives which differ, including any added or removed
+    /// from the end of the batch.
+    Modified {
+        index: usize,
+        old_kind: PrimitiveKind,
+        new_kind: PrimitiveKind,
+        old_len: usize,
+        new_len: usize,
+        changed_primitives: Option<usize>,
+    },
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResourcesDiff {
+    pub added_fonts: Vec<FontId>,
+    pub removed_fonts: Vec<FontId>,
+    pub added_textures: Vec<TextureId>,
+    pub removed_textures: Vec<TextureId>,
+}
+
+impl Scene {
+    /// Reports what changed going from this scene to `other`. Shared and mutable batches with the
+    /// same primitives are considered equal.
+    pub fn diff(&self, other: &Scene) -> SceneDiff {
+        let mut layers = Vec::new();
+        for index in 0..self.layers.len().max(other.layers.len()) {
+            match (self.layers.get(index), other.layers.get(index)) {
+                (Some(old), Some(new)) => {
+                    if old != new {
+                        layers.push(diff_layer(index, old, new));
+                    }
+                }
+                (None, Some(_)) => layers.push(LayerDiff::Added { index }),
+                (Some(_), None) => layers.push(LayerDiff::Removed { index }),
+                (None, None) => unreachable!(),
+            }
+        }
+
+        let (added_fonts, removed_fonts) =
+            diff_keys(&self.resources.fonts, &other.resources.fonts, FontId::raw);
+        let (added_textures, removed_textures) = diff_keys(
+            &self.resources.textures,
+            &other.resources.textures,
+            TextureId::raw,
+        );
+
+        SceneDiff {
+            layers,
+            resources: ResourcesDiff {
+                added_fonts,
+                removed_fonts,
+                added_textures,
+                removed_textures,
+            },
+        }
+    }
+}
+
+impl SceneDiff {
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty() && self.resources.is_empty()
+    }
+}
+
+impl ResourcesDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_fonts.is_empty()
+            && self.removed_fonts.is_empty()
+            && self.added_textures.is_empty()
+            && self.removed_textures.is_empty()
+    }
+}
+
+fn diff_layer(index: usize, old: &Layer, new: &Layer) -> LayerDiff {
+    let clip = (old.clip != new.clip).then_some(ClipChange {
+        old: old.clip,
+        new: new.clip,
+    });
+
+    let mask = match (&old.mask, &new.mask) {
+        (None, Some(_)) => Some(MaskChange::Added),
+        (Some(_), None) => Some(MaskChange::Removed),
+        (Some(old), Some(new)) if old != new => Some(MaskChange::Modified(diff_batches(old, new))),
+        _ => None,
+    };
+
+    LayerDiff::Modified {
+        index,
+        clip,
+        mask,
+        batches: diff_batches(&old.contents, &new.contents),
+    }
+}
+
+fn diff_batches(old: &LayerContents, new: &LayerContents) -> Vec<BatchDiff> {
+    let mut batches = Vec::new();
+    for index in 0..old.primitives.len().max(new.primitives.len()) {
+        match (old.primitives.get(index), new.primitives.get(index)) {
+            (Some(old), Some(new)) => {
+                if old != new {
+                    batches.push(BatchDiff::Modified {
+                        index,
+                        old_kind: old.kind(),
+                        new_kind: new.kind(),
+                        old_len: old.len(),
+                        new_len: new.len(),
+                        changed_primitives: changed_primitives(old, new),
+                    });
+                }
+            }
+            (None, Some(new)) => batches.push(BatchDiff::Added {
+                index,
+                kind: new.kind(),
+                len: new.len(),
+            }),
+            (Some(old), None) => batches.push(BatchDiff::Removed {
+                index,
+                kind: old.kind(),
+                len: old.len(),
+            }),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    batches
+}
+
+fn changed_primitives(old: &PrimitiveBatch, new: &PrimitiveBatch) -> Option<usize> {
+    fn count<T: PartialEq>(old: &[T], new: &[T]) -> usize {
+        let changed = old.iter().zip(new).filter(|(old, new)| old != new).count();
+        changed + old.len().abs_diff(new.len())
+    }
+
+    if let (Some(old), Some(new)) = (old.as_blur_vec(), new.as_blur_vec()) {
+        Some(count(old, new))
+    } else if let (Some(old), Some(new)) = (old.as_quad_vec(), new.as_quad_vec()) {
+        Some(count(old, new))
+    } else if let (Some(old), Some(new)) = (old.as_glyph_run_vec(), new.as_glyph_run_vec()) {
+        Some(count(old, new))
+    } else if let (Some(old), Some(new)) = (old.as_path_vec(), new.as_path_vec()) {
+        Some(count(old, new))
+    } else if let (Some(old), Some(new)) = (old.as_sprite_vec(), new.as_sprite_vec()) {
+        Some(count(old, new))
+    } else if let (Some(old), Some(new)) = (old.as_shadow_vec(), new.as_shadow_vec()) {
+        Some(count(old, new))
+    } else if let (Some(old), Some(new)) = (old.as_ellipse_vec(), new.as_ellipse_vec()) {
+        Some(count(old, new))
+    } else if let (Some(old), Some(new)) = (old.as_line_segment_vec(), new.as_line_segment_vec()) {
+        Some(count(old, new))
+    } else if let (Some(old), Some(new)) = (old.as_undercurl_vec(), new.as_undercurl_vec()) {
+        Some(count(old, new))
+    } else {
+        None
+    }
+}
+
+/// Returns the keys only in `new` and the keys only in `old`, sorted so the diff is stable.
+fn diff_keys<K: Copy + Eq + Hash, V>(
+    old: &HashMap<K, V>,
+    new: &HashMap<K, V>,
+    sort_key: fn(&K) -> u64,
+) -> (Vec<K>, Vec<K>) {
+    let mut added: Vec<K> = new
+        .keys()
+        .filter(|key| !old.contains_key(key))
+        .copied()
+        .collect();
+    let mut removed: Vec<K> = old
+        .keys()
+        .filter(|key| !new.contains_key(key))
+        .copied()
+        .collect();
+    added.sort_by_key(sort_key);
+    removed.sort_by_key(sort_key);
+    (added, removed)
+}
+
+impl Display for SceneDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+
+        for layer in &self.layers {
+            write!(f, "{}", layer)?;
+        }
+        write!(f, "{}", self.resources)
+    }
+}
+
+impl Display for LayerDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayerDiff::Added { index } => writeln!(f, "Layer {}: added", index),
+            LayerDiff::Removed { index } => writeln!(f, "Layer {}: removed", index),
+            LayerDiff::Modified {
+                index,
+                clip,
+                mask,
+                batches,
+            } => {
+                writeln!(f, "Layer {}: modified", index)?;
+                if let Some(ClipChange { old, new }) = clip {
+                    writeln!(f, "  clip: {:?} -> {:?}", old, new)?;
+                }
+                match mask {
+                    Some(MaskChange::Added) => writeln!(f, "  mask: added")?,
+                    Some(MaskChange::Removed) => writeln!(f, "  mask: removed")?,
+                    Some(MaskChange::Modified(batches)) => {
+                        for batch in batches {
+                            writeln!(f, "  mask {}", batch)?;
+                        }
+                    }
+                    None => {}
+                }
+                for batch in batches {
+                    writeln!(f, "  {}", batch)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for BatchDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchDiff::Added { index, kind, len } => {
+                write!(f, "batch {}: added {} ({})", index, kind, len)
+            }
+            BatchDiff::Removed { index, kind, len } => {
+                write!(f, "batch {}: removed {} ({})", index, kind, len)
+            }
+            BatchDiff::Modified {
+                index,
+                old_kind,
+                new_kind,
+                old_len,
+                new_len,
+                changed_primitives,
+            } => {
+                write!(
+                    f,
+                    "batch {}: {} ({}) -> {} ({})",
+                    index, old_kind, old_len, new_kind, new_len
+                )?;
+                if let Some(changed_primitives) = changed_primitives {
+                    write!(f, ", {} primitives changed", changed_primitives)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for ResourcesDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fonts = |ids: &[FontId]| ids.iter().map(FontId::raw).collect::<Vec<_>>();
+        let textures = |ids: &[TextureId]| ids.iter().map(TextureId::raw).collect::<Vec<_>>();
+        let lists = [
+            ("Fonts added", fonts(&self.added_fonts)),
+            ("Fonts removed", fonts(&self.removed_fonts)),
+            ("Textures added", textures(&self.added_textures)),
+            ("Textures removed", textures(&self.removed_textures)),
+        ];
+
+        for (label, ids) in lists {
+            if !ids.is_empty() {
+                writeln!(f, "{}: {:?}", label, ids)?;
+            }
+        }
+        Ok(())
+    }
+}
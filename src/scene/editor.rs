@@ -0,0 +1,50 @@
+use std::ops::Range;
+
+use palette::Srgba;
+
+/// How the parts of a text editor which aren't text are drawn by
+/// [`Layer::add_styled_editor`](super::Layer::add_styled_editor).
+#[derive(Clone, Debug, PartialEq)]
+pub struct EditorStyle {
+    /// The fill behind selected text.
+    pub selection_color: Srgba,
+    pub caret_color: Srgba,
+    /// The width of the caret. A width of zero hides the caret, which is how blinking is done.
+    pub caret_width: f32,
+    /// The byte range of the text an input method is composing. The preedit text is inserted
+    /// into the editor like any other text and underlined here until it is committed.
+    pub preedit: Option<Range<usize>>,
+}
+
+impl EditorStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_selection_color(mut self, color: Srgba) -> Self {
+        self.selection_color = color;
+        self
+    }
+
+    pub fn with_caret(mut self, width: f32, color: Srgba) -> Self {
+        self.caret_width = width;
+        self.caret_color = color;
+        self
+    }
+
+    pub fn with_preedit(mut self, preedit: Range<usize>) -> Self {
+        self.preedit = Some(preedit);
+        self
+    }
+}
+
+impl Default for EditorStyle {
+    fn default() -> Self {
+        Self {
+            selection_color: Srgba::new(0.2, 0.4, 0.9, 0.4),
+            caret_color: Srgba::new(0., 0., 0., 1.),
+            caret_width: 1.5,
+            preedit: None,
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use glam::Vec4;
+use glamour::{AsRaw, Point2, Rect, Size2, Vector2};
+use palette::Srgba;
+use serde::{Deserialize, Serialize};
+
+use crate::default_drawables::InstancedEllipse;
+
+/// An axis aligned ellipse drawn analytically with anti aliased edges. The stroke is centered on
+/// the edge of the ellipse and drawn over the fill, like an svg `<ellipse>`.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Ellipse {
+    pub center: Point2,
+    /// The horizontal and vertical radius.
+    pub radii: Vector2,
+    pub color: Srgba,
+    #[serde(default)]
+    pub stroke_width: f32,
+    #[serde(default)]
+    pub stroke_color: Srgba,
+}
+
+impl Ellipse {
+    pub fn new(center: Point2, radii: Vector2, color: Srgba) -> Self {
+        Self {
+            center,
+            radii,
+            color,
+            stroke_width: 0.0,
+            stroke_color: Srgba::new(0., 0., 0., 0.),
+        }
+    }
+
+    pub fn circle(center: Point2, radius: f32, color: Srgba) -> Self {
+        Self::new(center, Vector2::new(radius, radius), color)
+    }
+
+    pub fn with_stroke(mut self, width: f32, color: Srgba) -> Self {
+        self.stroke_width = width;
+        self.stroke_color = color;
+        self
+    }
+
+    /// The area covered by the ellipse including its stroke.
+    pub fn bounds(&self) -> Rect {
+        let half_stroke = self.stroke_width.max(0.) / 2.;
+        let extent = Vector2::new(
+            self.radii.x.abs() + half_stroke,
+            self.radii.y.abs() + half_stroke,
+        );
+        Rect::new(
+            self.center - extent,
+            Size2::new(extent.x * 2., extent.y * 2.),
+        )
+    }
+
+    pub fn to_instanced(&self) -> InstancedEllipse {
+        InstancedEllipse {
+            color: Vec4::from_array(self.color.into_linear().into()),
+            stroke_color: Vec4::from_array(self.stroke_color.into_linear().into()),
+            center: *self.center.as_raw(),
+            radii: self.radii.as_raw().abs(),
+            stroke_width: self.stroke_width.max(0.),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use super::{Layer, Resources, Scene, Synthesis};
+
+/// The version of the scene format written by this version of vide. Whenever a change to the
+/// scene types would cause older documents to fail to parse or to change meaning, bump this and
+/// append a migration to `MIGRATIONS` which upgrades documents from the previous version.
+pub const SCENE_FORMAT_VERSION: u32 = 1;
+
+type Migration = fn(&mut Map<String, Value>);
+
+// Migrations are indexed by the version they upgrade from, so MIGRATIONS[0] upgrades documents
+// written before the format was versioned to version 1.
+const MIGRATIONS: [Migration; SCENE_FORMAT_VERSION as usize] = [add_missing_glyph_run_synthesis];
+
+/// Upgrades a serialized scene document in place to the current `SCENE_FORMAT_VERSION` by
+/// applying every migration between the version recorded in the document and the current one.
+/// Documents without a `format_version` are treated as version 0.
+pub fn migrate_scene(document: &mut Value) -> Result<(), String> {
+    let Value::Object(document) = document else {
+        return Err("Scene document is not an object".to_string());
+    };
+
+    let version = match document.get("format_version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| format!("Invalid scene format version {}", version))?,
+    };
+
+    if version > SCENE_FORMAT_VERSION {
+        return Err(format!(
+            "Scene format version {} is newer than the supported version {}",
+            version, SCENE_FORMAT_VERSION
+        ));
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(document);
+    }
+    document.insert("format_version".to_string(), SCENE_FORMAT_VERSION.into());
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct SerializableScene<'a> {
+    format_version: u32,
+    layers: &'a [Layer],
+    resources: &'a Resources,
+}
+
+#[derive(Deserialize)]
+struct DeserializableScene {
+    layers: Vec<Layer>,
+    resources: Resources,
+}
+
+impl Serialize for Scene {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        SerializableScene {
+            format_version: SCENE_FORMAT_VERSION,
+            layers: &self.layers,
+            resources: &self.resources,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Scene {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut document = Value::deserialize(deserializer)?;
+        migrate_scene(&mut document).map_err(D::Error::custom)?;
+        let scene = DeserializableScene::deserialize(document).map_err(D::Error::custom)?;
+        Ok(Self {
+            layers: scene.layers,
+            resources: scene.resources,
+        })
+    }
+}
+
+/// Calls `update` on every primitive batch in the document, including those in layer masks.
+fn for_each_batch(document: &mut Map<String, Value>, mut update: impl FnMut(&mut Value)) {
+    let Some(Value::Array(layers)) = document.get_mut("layers") else {
+        return;
+    };
+
+    for layer in layers.iter_mut() {
+        let mask_primitives = layer
+            .get_mut("mask")
+            .and_then(|mask| mask.get_mut("primitives"));
+        if let Some(Value::Array(batches)) = mask_primitives {
+            batches.iter_mut().for_each(&mut update);
+        }
+
+        if let Some(Value::Array(batches)) = layer.get_mut("primitives") {
+            batches.iter_mut().for_each(&mut update);
+        }
+    }
+}
+
+// Version 0 documents could contain glyph runs serialized before font synthesis was stored on
+// the run.
+fn add_missing_glyph_run_synthesis(document: &mut Map<String, Value>) {
+    let default_synthesis =
+        serde_json::to_value(Synthesis::default()).expect("Could not serialize synthesis");
+
+    for_each_batch(document, |batch| {
+        let Some(Value::Array(glyph_runs)) = batch.get_mut("GlyphRuns") else {
+            return;
+        };
+
+        for glyph_run in glyph_runs.iter_mut() {
+            if let Value::Object(glyph_run) = glyph_run {
+                glyph_run
+                    .entry("synthesis")
+                    .or_insert_with(|| default_synthesis.clone());
+            }
+        }
+    });
+}
@@ -37,7 +37,7 @@ impl From<&Setting> for swash::Setting<f32> {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct Synthesis {
     pub vars: Vec<Setting>,
     pub embolden: bool,
@@ -57,7 +57,7 @@ impl From<swash::Synthesis> for Synthesis {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct GlyphRun {
     pub position: Point2,
     pub font_id: FontId,
@@ -69,7 +69,7 @@ pub struct GlyphRun {
     pub glyphs: Vec<Glyph>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Glyph {
     pub id: u16,
     pub offset: Vector2,
@@ -78,11 +78,14 @@ pub struct Glyph {
 #[derive(Copy, Clone, Debug, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct FontId(u64);
 
-#[cfg(test)]
 impl FontId {
-    pub fn new(id: u64) -> Self {
+    pub(crate) fn new(id: u64) -> Self {
         Self(id)
     }
+
+    pub(crate) fn raw(&self) -> u64 {
+        self.0
+    }
 }
 
 impl Resources {
@@ -113,7 +116,9 @@ impl<'a> Deserialize<'a> for Font {
         D: serde::Deserializer<'a>,
     {
         let data = String::deserialize(deserializer)?;
-        let data = BASE64_STANDARD.decode(data).unwrap();
+        let data = BASE64_STANDARD
+            .decode(data)
+            .map_err(serde::de::Error::custom)?;
         Ok(Self { data: data.into() })
     }
 }
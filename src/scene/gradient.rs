@@ -0,0 +1,105 @@
+use glamour::{Point2, Rect};
+use palette::Srgba;
+use serde::{Deserialize, Serialize};
+
+/// The most stops a gradient can have. The stops are packed into the quad instance data, so quads
+/// with gradients with more stops are never drawn, and are reported by `Scene::validate`.
+pub const MAX_GRADIENT_STOPS: usize = 4;
+
+/// A gradient filling a quad in place of its color.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Gradient {
+    pub shape: GradientShape,
+    pub stops: Vec<GradientStop>,
+    #[serde(default)]
+    pub interpolation: GradientInterpolation,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum GradientShape {
+    /// A gradient along a line through the center of the quad at `angle` degrees clockwise from
+    /// pointing up, like css `linear-gradient`. The line is long enough for the corners to reach
+    /// the first and last stops.
+    Linear { angle: f32 },
+    /// A circular gradient around `center`, relative to the top left of the quad, reaching the
+    /// last stop at `radius`.
+    Radial { center: Point2, radius: f32 },
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct GradientStop {
+    /// The position of the stop along the gradient from 0 to 1.
+    pub offset: f32,
+    pub color: Srgba,
+}
+
+/// The color space colors are mixed in between stops.
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum GradientInterpolation {
+    /// Mixes linear rgb, which keeps the brightness of the blend physically correct.
+    #[default]
+    Linear,
+    /// Mixes in the Oklab color space, which looks more even to the eye and avoids the grey
+    /// midpoints linear rgb produces between saturated colors.
+    Perceptual,
+}
+
+impl Gradient {
+    pub fn linear(angle: f32, stops: impl IntoIterator<Item = GradientStop>) -> Self {
+        Self {
+            shape: GradientShape::Linear { angle },
+            stops: stops.into_iter().collect(),
+            interpolation: GradientInterpolation::default(),
+        }
+    }
+
+    pub fn radial(
+        center: Point2,
+        radius: f32,
+        stops: impl IntoIterator<Item = GradientStop>,
+    ) -> Self {
+        Self {
+            shape: GradientShape::Radial { center, radius },
+            stops: stops.into_iter().collect(),
+            interpolation: GradientInterpolation::default(),
+        }
+    }
+
+    pub fn with_interpolation(mut self, interpolation: GradientInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// The geometry of the gradient in the same coordinates as `region`. For linear gradients this
+    /// is the start and end of the gradient line, and for radial gradients it is the center, the
+    /// radius and a zero.
+    pub(crate) fn parameters(&self, region: Rect) -> [f32; 4] {
+        match self.shape {
+            GradientShape::Linear { angle } => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let (width, height) = (region.size.width, region.size.height);
+                let half_length = (width * sin.abs() + height * cos.abs()) / 2.;
+                let center_x = region.origin.x + width / 2.;
+                let center_y = region.origin.y + height / 2.;
+                [
+                    center_x - sin * half_length,
+                    center_y + cos * half_length,
+                    center_x + sin * half_length,
+                    center_y - cos * half_length,
+                ]
+            }
+            GradientShape::Radial { center, radius } => [
+                region.origin.x + center.x,
+                region.origin.y + center.y,
+                radius,
+                0.,
+            ],
+        }
+    }
+}
+
+impl GradientStop {
+    pub fn new(offset: f32, color: Srgba) -> Self {
+        Self { offset, color }
+    }
+}
@@ -1,16 +1,21 @@
-use std::sync::Arc;
+use std::{cell::RefCell, collections::HashMap, ops::Range, sync::Arc};
 
 use glamour::{point2, size2, vec2, Point2, Rect};
+use ordered_float::OrderedFloat;
 use palette::Srgba;
 use parley::{
     layout::{editor::PlainEditor, Line, PositionedLayoutItem},
+    swash::{scale::ScaleContext, zeno::Bounds, FontRef, GlyphId},
     Layout,
 };
 use serde::{Deserialize, Serialize};
 
-use super::{Blur, Glyph, GlyphRun, Path, Quad, Resources, Sprite, TextureId};
+use super::{
+    Blur, EditorStyle, Ellipse, FontId, Glyph, GlyphRun, LineSegment, Path, Quad, Resources,
+    Shadow, Sprite, Synthesis, TextureId, Undercurl,
+};
 
-#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[derive(Clone, Debug, Deserialize, Serialize, Default, PartialEq)]
 pub struct Layer {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -108,6 +113,78 @@ impl Layer {
         self
     }
 
+    pub fn add_shadow(&mut self, shadow: Shadow) {
+        self.contents.add_shadow(shadow);
+    }
+
+    pub fn with_shadow(mut self, shadow: Shadow) -> Self {
+        self.add_shadow(shadow);
+        self
+    }
+
+    pub fn add_shadows(&mut self, shadows: Arc<Vec<Shadow>>) {
+        self.contents.add_shadows(shadows);
+    }
+
+    pub fn with_shadows(mut self, shadows: Arc<Vec<Shadow>>) -> Self {
+        self.add_shadows(shadows);
+        self
+    }
+
+    pub fn add_ellipse(&mut self, ellipse: Ellipse) {
+        self.contents.add_ellipse(ellipse);
+    }
+
+    pub fn with_ellipse(mut self, ellipse: Ellipse) -> Self {
+        self.add_ellipse(ellipse);
+        self
+    }
+
+    pub fn add_ellipses(&mut self, ellipses: Arc<Vec<Ellipse>>) {
+        self.contents.add_ellipses(ellipses);
+    }
+
+    pub fn with_ellipses(mut self, ellipses: Arc<Vec<Ellipse>>) -> Self {
+        self.add_ellipses(ellipses);
+        self
+    }
+
+    pub fn add_line_segment(&mut self, line_segment: LineSegment) {
+        self.contents.add_line_segment(line_segment);
+    }
+
+    pub fn with_line_segment(mut self, line_segment: LineSegment) -> Self {
+        self.add_line_segment(line_segment);
+        self
+    }
+
+    pub fn add_line_segments(&mut self, line_segments: Arc<Vec<LineSegment>>) {
+        self.contents.add_line_segments(line_segments);
+    }
+
+    pub fn with_line_segments(mut self, line_segments: Arc<Vec<LineSegment>>) -> Self {
+        self.add_line_segments(line_segments);
+        self
+    }
+
+    pub fn add_undercurl(&mut self, undercurl: Undercurl) {
+        self.contents.add_undercurl(undercurl);
+    }
+
+    pub fn with_undercurl(mut self, undercurl: Undercurl) -> Self {
+        self.add_undercurl(undercurl);
+        self
+    }
+
+    pub fn add_undercurls(&mut self, undercurls: Arc<Vec<Undercurl>>) {
+        self.contents.add_undercurls(undercurls);
+    }
+
+    pub fn with_undercurls(mut self, undercurls: Arc<Vec<Undercurl>>) -> Self {
+        self.add_undercurls(undercurls);
+        self
+    }
+
     pub fn add_glyph_run(&mut self, glyph_run: GlyphRun) {
         self.contents.add_glyph_run(glyph_run);
     }
@@ -162,6 +239,8 @@ impl Layer {
         self
     }
 
+    /// Adds only the text of an editor. Use [`Layer::add_styled_editor`] to draw its selection,
+    /// caret and preedit as well.
     pub fn add_editor(
         &mut self,
         resources: &mut Resources,
@@ -183,6 +262,61 @@ impl Layer {
         self
     }
 
+    /// Adds the selection highlight behind the text of an editor, then the text with the preedit
+    /// underlined, then the caret on top.
+    pub fn add_styled_editor(
+        &mut self,
+        resources: &mut Resources,
+        editor: PlainEditor<Srgba>,
+        style: &EditorStyle,
+        position: Point2,
+    ) {
+        let editor_rect = |x0: f64, y0: f64, x1: f64, y1: f64| {
+            Rect::new(
+                position + vec2!(x0 as f32, y0 as f32),
+                size2!((x1 - x0) as f32, (y1 - y0) as f32),
+            )
+        };
+
+        for rect in editor.selection_geometry() {
+            self.add_quad(Quad::new(
+                editor_rect(rect.x0, rect.y0, rect.x1, rect.y1),
+                style.selection_color,
+            ));
+        }
+
+        let mut preedit_underlines = Vec::new();
+        for line in editor.lines() {
+            if let Some(preedit) = &style.preedit {
+                preedit_underlines.extend(preedit_underlines(&line, preedit, position));
+            }
+            self.add_line(resources, line, position);
+        }
+        for underline in preedit_underlines {
+            self.add_line_segment(underline);
+        }
+
+        if style.caret_width > 0. {
+            if let Some(caret) = editor.selection_strong_geometry(style.caret_width) {
+                self.add_quad(Quad::new(
+                    editor_rect(caret.x0, caret.y0, caret.x1, caret.y1),
+                    style.caret_color,
+                ));
+            }
+        }
+    }
+
+    pub fn with_styled_editor(
+        mut self,
+        resources: &mut Resources,
+        editor: PlainEditor<Srgba>,
+        style: &EditorStyle,
+        position: Point2,
+    ) -> Self {
+        self.add_styled_editor(resources, editor, style, position);
+        self
+    }
+
     pub fn add_text_layout(
         &mut self,
         resources: &mut Resources,
@@ -204,7 +338,11 @@ impl Layer {
         self
     }
 
+    /// Adds the glyph runs of a laid out line along with their underlines and strikethroughs.
+    /// Decorations are placed using the offsets and sizes from the style when set and from the
+    /// font metrics otherwise, and underlines are broken around descenders which cross them.
     pub fn add_line(&mut self, resources: &mut Resources, line: Line<Srgba>, position: Point2) {
+        let mut decorations = Vec::new();
         for item in line.items() {
             let PositionedLayoutItem::GlyphRun(glyph_run) = item else {
                 continue;
@@ -229,7 +367,7 @@ impl Layer {
                 current_x += glyph.advance;
             }
 
-            self.add_glyph_run(GlyphRun {
+            let run_primitive = GlyphRun {
                 position: position + vec2!(glyph_run.offset(), glyph_run.baseline()),
                 font_id,
                 font_index,
@@ -238,7 +376,49 @@ impl Layer {
                 normalized_coords,
                 glyphs,
                 synthesis,
-            });
+            };
+
+            // Offsets are measured up from the baseline to the top of the decoration
+            let metrics = run.metrics();
+            let start = run_primitive.position.x;
+            let end = start + glyph_run.advance();
+            let baseline = run_primitive.position.y;
+            if let Some(underline) = &style.underline {
+                let offset = underline.offset.unwrap_or(metrics.underline_offset);
+                let thickness = underline.size.unwrap_or(metrics.underline_size);
+                let y = baseline - offset + thickness / 2.;
+                for (start, end) in skip_ink(
+                    resources,
+                    &run_primitive,
+                    (y - thickness, y + thickness),
+                    (start, end),
+                ) {
+                    decorations.push(LineSegment::new(
+                        point2!(start, y),
+                        point2!(end, y),
+                        thickness,
+                        underline.brush,
+                    ));
+                }
+            }
+            if let Some(strikethrough) = &style.strikethrough {
+                let offset = strikethrough.offset.unwrap_or(metrics.strikethrough_offset);
+                let thickness = strikethrough.size.unwrap_or(metrics.strikethrough_size);
+                let y = baseline - offset + thickness / 2.;
+                decorations.push(LineSegment::new(
+                    point2!(start, y),
+                    point2!(end, y),
+                    thickness,
+                    strikethrough.brush,
+                ));
+            }
+
+            self.add_glyph_run(run_primitive);
+        }
+
+        // Decorations are added after the glyph runs so the whole line stays in two batches
+        for decoration in decorations {
+            self.add_line_segment(decoration);
         }
     }
 
@@ -253,7 +433,160 @@ impl Layer {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+/// The underlines below the part of each glyph run in a line which falls in the preedit range.
+/// Clusters are walked in visual order so the underline ends up under the right glyphs in right
+/// to left text as well.
+fn preedit_underlines(
+    line: &Line<Srgba>,
+    preedit: &Range<usize>,
+    position: Point2,
+) -> Vec<LineSegment> {
+    let mut underlines = Vec::new();
+    for item in line.items() {
+        let PositionedLayoutItem::GlyphRun(glyph_run) = item else {
+            continue;
+        };
+        let run = glyph_run.run();
+        let mut x = position.x + glyph_run.offset();
+        let mut span: Option<(f32, f32)> = None;
+        for cluster in run.visual_clusters() {
+            let advance = cluster.advance();
+            let text_range = cluster.text_range();
+            if text_range.start < preedit.end && preedit.start < text_range.end {
+                let (start, _) = span.unwrap_or((x, x));
+                span = Some((start, x + advance));
+            }
+            x += advance;
+        }
+
+        if let Some((start, end)) = span {
+            let metrics = run.metrics();
+            let thickness = metrics.underline_size.max(1.);
+            let y = position.y + glyph_run.baseline() - metrics.underline_offset + thickness / 2.;
+            underlines.push(LineSegment::new(
+                point2!(start, y),
+                point2!(end, y),
+                thickness,
+                glyph_run.style().brush,
+            ));
+        }
+    }
+    underlines
+}
+
+// Splits the horizontal span of an underline into the pieces which don't cross any glyph's ink,
+// like css `text-decoration-skip-ink`. Glyph bounds are used in place of the exact outlines,
+// which is close enough for the descenders this is meant for.
+fn skip_ink(
+    resources: &Resources,
+    glyph_run: &GlyphRun,
+    (top, bottom): (f32, f32),
+    (start, end): (f32, f32),
+) -> Vec<(f32, f32)> {
+    let Some(font_ref) = resources
+        .fonts
+        .get(&glyph_run.font_id)
+        .and_then(|font| font.as_swash_font_ref(glyph_run.font_index))
+    else {
+        return vec![(start, end)];
+    };
+    let glyph_bounds = GLYPH_BOUNDS.with_borrow_mut(|cache| cache.bounds(font_ref, glyph_run));
+
+    // The gap around each glyph matches the space the underline keeps from the ink vertically
+    let padding = (bottom - top) / 2.;
+    let mut pieces = vec![(start, end)];
+    for (glyph, bounds) in glyph_run.glyphs.iter().zip(glyph_bounds) {
+        let Some(bounds) = bounds else {
+            continue;
+        };
+        // Outlines are y up with the origin on the baseline
+        let origin = glyph_run.position + glyph.offset;
+        if origin.y - bounds.min.y < top || origin.y - bounds.max.y > bottom {
+            continue;
+        }
+
+        let gap_start = origin.x + bounds.min.x - padding;
+        let gap_end = origin.x + bounds.max.x + padding;
+        pieces = pieces
+            .into_iter()
+            .flat_map(|(start, end)| [(start, end.min(gap_start)), (start.max(gap_end), end)])
+            .filter(|(start, end)| end > start)
+            .collect();
+    }
+    pieces
+}
+
+thread_local! {
+    // Text is usually laid out again every frame, so the scaler context and the outline bounds are
+    // kept between calls to add_line instead of scaling the same glyphs each time
+    static GLYPH_BOUNDS: RefCell<GlyphBoundsCache> = RefCell::new(GlyphBoundsCache::new());
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct RunKey {
+    font_id: FontId,
+    font_index: usize,
+    size: OrderedFloat<f32>,
+    normalized_coords: Vec<i16>,
+    synthesis: Synthesis,
+}
+
+struct GlyphBoundsCache {
+    scale_context: ScaleContext,
+    runs: HashMap<RunKey, HashMap<GlyphId, Option<Bounds>>>,
+}
+
+impl GlyphBoundsCache {
+    // Enough for the fonts and sizes of a typical ui. The cache is emptied once it holds more so it
+    // doesn't grow without bound when sizes are animated.
+    const MAX_RUNS: usize = 256;
+
+    fn new() -> Self {
+        Self {
+            scale_context: ScaleContext::new(),
+            runs: HashMap::new(),
+        }
+    }
+
+    /// The outline bounds of each glyph in the run, or None for glyphs without an outline.
+    fn bounds(&mut self, font_ref: FontRef, glyph_run: &GlyphRun) -> Vec<Option<Bounds>> {
+        let key = RunKey {
+            font_id: glyph_run.font_id,
+            font_index: glyph_run.font_index,
+            size: glyph_run.size.into(),
+            normalized_coords: glyph_run.normalized_coords.clone(),
+            synthesis: glyph_run.synthesis.clone(),
+        };
+        if self.runs.len() >= Self::MAX_RUNS && !self.runs.contains_key(&key) {
+            self.runs.clear();
+        }
+
+        let glyphs = self.runs.entry(key).or_default();
+        // Only built once a glyph is missing from the cache
+        let mut scaler = None;
+        glyph_run
+            .glyphs
+            .iter()
+            .map(|glyph| {
+                *glyphs.entry(glyph.id).or_insert_with(|| {
+                    let scaler = scaler.get_or_insert_with(|| {
+                        self.scale_context
+                            .builder(font_ref)
+                            .size(glyph_run.size)
+                            .variations(&glyph_run.synthesis.vars)
+                            .normalized_coords(&glyph_run.normalized_coords)
+                            .build()
+                    });
+                    scaler
+                        .scale_outline(glyph.id)
+                        .map(|outline| outline.bounds())
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default, PartialEq)]
 pub struct LayerContents {
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -359,6 +692,89 @@ impl LayerContents {
                 sprites,
             )));
     }
+
+    pub fn add_shadow(&mut self, shadow: Shadow) {
+        match self.primitives.last_mut() {
+            Some(PrimitiveBatch::Mutable(MutablePrimitiveBatch::Shadows(shadows))) => {
+                shadows.push(shadow);
+            }
+            _ => {
+                self.primitives
+                    .push(PrimitiveBatch::Mutable(MutablePrimitiveBatch::Shadows(
+                        vec![shadow],
+                    )));
+            }
+        }
+    }
+
+    pub fn add_shadows(&mut self, shadows: Arc<Vec<Shadow>>) {
+        self.primitives
+            .push(PrimitiveBatch::Shared(SharedPrimitiveBatch::Shadows(
+                shadows,
+            )));
+    }
+
+    pub fn add_ellipse(&mut self, ellipse: Ellipse) {
+        match self.primitives.last_mut() {
+            Some(PrimitiveBatch::Mutable(MutablePrimitiveBatch::Ellipses(ellipses))) => {
+                ellipses.push(ellipse);
+            }
+            _ => {
+                self.primitives
+                    .push(PrimitiveBatch::Mutable(MutablePrimitiveBatch::Ellipses(
+                        vec![ellipse],
+                    )));
+            }
+        }
+    }
+
+    pub fn add_ellipses(&mut self, ellipses: Arc<Vec<Ellipse>>) {
+        self.primitives
+            .push(PrimitiveBatch::Shared(SharedPrimitiveBatch::Ellipses(
+                ellipses,
+            )));
+    }
+
+    pub fn add_line_segment(&mut self, line_segment: LineSegment) {
+        match self.primitives.last_mut() {
+            Some(PrimitiveBatch::Mutable(MutablePrimitiveBatch::LineSegments(line_segments))) => {
+                line_segments.push(line_segment);
+            }
+            _ => {
+                self.primitives.push(PrimitiveBatch::Mutable(
+                    MutablePrimitiveBatch::LineSegments(vec![line_segment]),
+                ));
+            }
+        }
+    }
+
+    pub fn add_line_segments(&mut self, line_segments: Arc<Vec<LineSegment>>) {
+        self.primitives
+            .push(PrimitiveBatch::Shared(SharedPrimitiveBatch::LineSegments(
+                line_segments,
+            )));
+    }
+
+    pub fn add_undercurl(&mut self, undercurl: Undercurl) {
+        match self.primitives.last_mut() {
+            Some(PrimitiveBatch::Mutable(MutablePrimitiveBatch::Undercurls(undercurls))) => {
+                undercurls.push(undercurl);
+            }
+            _ => {
+                self.primitives
+                    .push(PrimitiveBatch::Mutable(MutablePrimitiveBatch::Undercurls(
+                        vec![undercurl],
+                    )));
+            }
+        }
+    }
+
+    pub fn add_undercurls(&mut self, undercurls: Arc<Vec<Undercurl>>) {
+        self.primitives
+            .push(PrimitiveBatch::Shared(SharedPrimitiveBatch::Undercurls(
+                undercurls,
+            )));
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -368,6 +784,59 @@ pub enum PrimitiveBatch {
 }
 
 impl PrimitiveBatch {
+    pub fn kind(&self) -> PrimitiveKind {
+        match self {
+            Self::Mutable(MutablePrimitiveBatch::Blurs(_))
+            | Self::Shared(SharedPrimitiveBatch::Blurs(_)) => PrimitiveKind::Blurs,
+            Self::Mutable(MutablePrimitiveBatch::Quads(_))
+            | Self::Shared(SharedPrimitiveBatch::Quads(_)) => PrimitiveKind::Quads,
+            Self::Mutable(MutablePrimitiveBatch::GlyphRuns(_))
+            | Self::Shared(SharedPrimitiveBatch::GlyphRuns(_)) => PrimitiveKind::GlyphRuns,
+            Self::Mutable(MutablePrimitiveBatch::Paths(_))
+            | Self::Shared(SharedPrimitiveBatch::Paths(_)) => PrimitiveKind::Paths,
+            Self::Mutable(MutablePrimitiveBatch::Sprites(_))
+            | Self::Shared(SharedPrimitiveBatch::Sprites(_)) => PrimitiveKind::Sprites,
+            Self::Mutable(MutablePrimitiveBatch::Shadows(_))
+            | Self::Shared(SharedPrimitiveBatch::Shadows(_)) => PrimitiveKind::Shadows,
+            Self::Mutable(MutablePrimitiveBatch::Ellipses(_))
+            | Self::Shared(SharedPrimitiveBatch::Ellipses(_)) => PrimitiveKind::Ellipses,
+            Self::Mutable(MutablePrimitiveBatch::LineSegments(_))
+            | Self::Shared(SharedPrimitiveBatch::LineSegments(_)) => PrimitiveKind::LineSegments,
+            Self::Mutable(MutablePrimitiveBatch::Undercurls(_))
+            | Self::Shared(SharedPrimitiveBatch::Undercurls(_)) => PrimitiveKind::Undercurls,
+        }
+    }
+
+    /// The number of primitives in the batch.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Mutable(MutablePrimitiveBatch::Blurs(blurs)) => blurs.len(),
+            Self::Shared(SharedPrimitiveBatch::Blurs(blurs)) => blurs.len(),
+            Self::Mutable(MutablePrimitiveBatch::Quads(quads)) => quads.len(),
+            Self::Shared(SharedPrimitiveBatch::Quads(quads)) => quads.len(),
+            Self::Mutable(MutablePrimitiveBatch::GlyphRuns(glyph_runs)) => glyph_runs.len(),
+            Self::Shared(SharedPrimitiveBatch::GlyphRuns(glyph_runs)) => glyph_runs.len(),
+            Self::Mutable(MutablePrimitiveBatch::Paths(paths)) => paths.len(),
+            Self::Shared(SharedPrimitiveBatch::Paths(paths)) => paths.len(),
+            Self::Mutable(MutablePrimitiveBatch::Sprites(sprites)) => sprites.len(),
+            Self::Shared(SharedPrimitiveBatch::Sprites(sprites)) => sprites.len(),
+            Self::Mutable(MutablePrimitiveBatch::Shadows(shadows)) => shadows.len(),
+            Self::Shared(SharedPrimitiveBatch::Shadows(shadows)) => shadows.len(),
+            Self::Mutable(MutablePrimitiveBatch::Ellipses(ellipses)) => ellipses.len(),
+            Self::Shared(SharedPrimitiveBatch::Ellipses(ellipses)) => ellipses.len(),
+            Self::Mutable(MutablePrimitiveBatch::LineSegments(line_segments)) => {
+                line_segments.len()
+            }
+            Self::Shared(SharedPrimitiveBatch::LineSegments(line_segments)) => line_segments.len(),
+            Self::Mutable(MutablePrimitiveBatch::Undercurls(undercurls)) => undercurls.len(),
+            Self::Shared(SharedPrimitiveBatch::Undercurls(undercurls)) => undercurls.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     pub fn is_blurs(&self) -> bool {
         matches!(
             self,
@@ -447,6 +916,87 @@ impl PrimitiveBatch {
             _ => None,
         }
     }
+
+    pub fn is_shadows(&self) -> bool {
+        matches!(
+            self,
+            Self::Mutable(MutablePrimitiveBatch::Shadows(_))
+                | Self::Shared(SharedPrimitiveBatch::Shadows(_))
+        )
+    }
+
+    pub fn as_shadow_vec(&self) -> Option<&Vec<Shadow>> {
+        match self {
+            Self::Mutable(MutablePrimitiveBatch::Shadows(shadows)) => Some(shadows),
+            Self::Shared(SharedPrimitiveBatch::Shadows(shadows)) => Some(shadows),
+            _ => None,
+        }
+    }
+
+    pub fn is_ellipses(&self) -> bool {
+        matches!(
+            self,
+            Self::Mutable(MutablePrimitiveBatch::Ellipses(_))
+                | Self::Shared(SharedPrimitiveBatch::Ellipses(_))
+        )
+    }
+
+    pub fn as_ellipse_vec(&self) -> Option<&Vec<Ellipse>> {
+        match self {
+            Self::Mutable(MutablePrimitiveBatch::Ellipses(ellipses)) => Some(ellipses),
+            Self::Shared(SharedPrimitiveBatch::Ellipses(ellipses)) => Some(ellipses),
+            _ => None,
+        }
+    }
+
+    pub fn is_line_segments(&self) -> bool {
+        matches!(
+            self,
+            Self::Mutable(MutablePrimitiveBatch::LineSegments(_))
+                | Self::Shared(SharedPrimitiveBatch::LineSegments(_))
+        )
+    }
+
+    pub fn as_line_segment_vec(&self) -> Option<&Vec<LineSegment>> {
+        match self {
+            Self::Mutable(MutablePrimitiveBatch::LineSegments(line_segments)) => {
+                Some(line_segments)
+            }
+            Self::Shared(SharedPrimitiveBatch::LineSegments(line_segments)) => Some(line_segments),
+            _ => None,
+        }
+    }
+
+    pub fn is_undercurls(&self) -> bool {
+        matches!(
+            self,
+            Self::Mutable(MutablePrimitiveBatch::Undercurls(_))
+                | Self::Shared(SharedPrimitiveBatch::Undercurls(_))
+        )
+    }
+
+    pub fn as_undercurl_vec(&self) -> Option<&Vec<Undercurl>> {
+        match self {
+            Self::Mutable(MutablePrimitiveBatch::Undercurls(undercurls)) => Some(undercurls),
+            Self::Shared(SharedPrimitiveBatch::Undercurls(undercurls)) => Some(undercurls),
+            _ => None,
+        }
+    }
+}
+
+// Shared and mutable batches compare equal when they contain the same primitives
+impl PartialEq for PrimitiveBatch {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_blur_vec() == other.as_blur_vec()
+            && self.as_quad_vec() == other.as_quad_vec()
+            && self.as_glyph_run_vec() == other.as_glyph_run_vec()
+            && self.as_path_vec() == other.as_path_vec()
+            && self.as_sprite_vec() == other.as_sprite_vec()
+            && self.as_shadow_vec() == other.as_shadow_vec()
+            && self.as_ellipse_vec() == other.as_ellipse_vec()
+            && self.as_line_segment_vec() == other.as_line_segment_vec()
+            && self.as_undercurl_vec() == other.as_undercurl_vec()
+    }
 }
 
 impl Serialize for PrimitiveBatch {
@@ -472,22 +1022,49 @@ impl<'de> Deserialize<'de> for PrimitiveBatch {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PrimitiveKind {
+    Blurs,
+    Quads,
+    GlyphRuns,
+    Paths,
+    Sprites,
+    Shadows,
+    Ellipses,
+    LineSegments,
+    Undercurls,
+}
+
+impl std::fmt::Display for PrimitiveKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub enum MutablePrimitiveBatch {
     Blurs(Vec<Blur>),
     Quads(Vec<Quad>),
     GlyphRuns(Vec<GlyphRun>),
     Paths(Vec<Path>),
     Sprites(Vec<Sprite<TextureId>>),
+    Shadows(Vec<Shadow>),
+    Ellipses(Vec<Ellipse>),
+    LineSegments(Vec<LineSegment>),
+    Undercurls(Vec<Undercurl>),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum SharedPrimitiveBatch {
     Blurs(Arc<Vec<Blur>>),
     Quads(Arc<Vec<Quad>>),
     GlyphRuns(Arc<Vec<GlyphRun>>),
     Paths(Arc<Vec<Path>>),
     Sprites(Arc<Vec<Sprite<TextureId>>>),
+    Shadows(Arc<Vec<Shadow>>),
+    Ellipses(Arc<Vec<Ellipse>>),
+    LineSegments(Arc<Vec<LineSegment>>),
+    Undercurls(Arc<Vec<Undercurl>>),
 }
 
 impl SharedPrimitiveBatch {
@@ -498,6 +1075,12 @@ impl SharedPrimitiveBatch {
             Self::GlyphRuns(glyph_runs) => MutablePrimitiveBatch::GlyphRuns(glyph_runs.to_vec()),
             Self::Paths(paths) => MutablePrimitiveBatch::Paths(paths.to_vec()),
             Self::Sprites(sprites) => MutablePrimitiveBatch::Sprites(sprites.to_vec()),
+            Self::Shadows(shadows) => MutablePrimitiveBatch::Shadows(shadows.to_vec()),
+            Self::Ellipses(ellipses) => MutablePrimitiveBatch::Ellipses(ellipses.to_vec()),
+            Self::LineSegments(line_segments) => {
+                MutablePrimitiveBatch::LineSegments(line_segments.to_vec())
+            }
+            Self::Undercurls(undercurls) => MutablePrimitiveBatch::Undercurls(undercurls.to_vec()),
         }
     }
 }
@@ -0,0 +1,68 @@
+use glam::Vec4;
+use glamour::{AsRaw, Point2, Rect, Size2};
+use palette::Srgba;
+use serde::{Deserialize, Serialize};
+
+use super::LineCap;
+use crate::default_drawables::InstancedLineSegment;
+
+/// A straight line segment drawn as a single instanced quad with analytically anti aliased edges.
+/// Much cheaper than a stroked `Path` for underlines, separators and grid lines since nothing
+/// needs to be tessellated.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct LineSegment {
+    pub start: Point2,
+    pub end: Point2,
+    pub width: f32,
+    pub color: Srgba,
+    #[serde(default)]
+    pub cap: LineCap,
+}
+
+impl LineSegment {
+    pub fn new(start: Point2, end: Point2, width: f32, color: Srgba) -> Self {
+        Self {
+            start,
+            end,
+            width,
+            color,
+            cap: LineCap::default(),
+        }
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// The area covered by the segment including its caps.
+    pub fn bounds(&self) -> Rect {
+        // Caps extend at most half the width past the end points in any direction
+        let extent = self.width.max(0.) / 2.;
+        let min = Point2::new(
+            self.start.x.min(self.end.x) - extent,
+            self.start.y.min(self.end.y) - extent,
+        );
+        let max = Point2::new(
+            self.start.x.max(self.end.x) + extent,
+            self.start.y.max(self.end.y) + extent,
+        );
+        Rect::new(min, Size2::new(max.x - min.x, max.y - min.y))
+    }
+
+    pub fn to_instanced(&self) -> InstancedLineSegment {
+        let cap = match self.cap {
+            LineCap::Butt => 0,
+            LineCap::Round => 1,
+            LineCap::Square => 2,
+        };
+        InstancedLineSegment {
+            color: Vec4::from_array(self.color.into_linear().into()),
+            start: *self.start.as_raw(),
+            end: *self.end.as_raw(),
+            width: self.width.max(0.),
+            cap,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use std::{collections::HashMap, fmt, fs, io, sync::Arc};
+
+use glamour::Size2;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{
+    check_scene_schema, is_binary_scene, migrate_scene, Font, FontId, Layer, NoResourceResolver,
+    ResourceData, ResourceDataError, ResourceDirectory, ResourceReference, ResourceResolver,
+    Resources, Scene, Texture, TextureId, SCENE_FORMAT_VERSION,
+};
+
+/// Identifies a font or texture in the scene resources.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ResourceId {
+    Font(FontId),
+    Texture(TextureId),
+}
+
+impl fmt::Display for ResourceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceId::Font(id) => write!(f, "font {}", id.raw()),
+            ResourceId::Texture(id) => write!(f, "texture {}", id.raw()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SceneLoadError {
+    Io(io::Error),
+    /// The json was malformed or didn't match the scene types. Line and column are 1 based, and
+    /// are 0 if the error couldn't be attributed to a position in the file.
+    Json {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// The scene was written by a newer version of vide, or its binary encoding is corrupt.
+    Format(String),
+    InvalidBase64 {
+        resource: ResourceId,
+        message: String,
+    },
+    /// The resource resolver could not provide the data for a referenced resource.
+    UnresolvedResource {
+        resource: ResourceId,
+        reference: ResourceReference,
+        error: io::Error,
+    },
+    /// The texture data doesn't contain exactly `size.width * size.height` rgba pixels.
+    TextureSizeMismatch {
+        texture: TextureId,
+        size: Size2<u32>,
+        data_length: usize,
+    },
+    /// A primitive in the given layer refers to a resource which isn't in the scene resources.
+    UnknownResource {
+        layer: usize,
+        resource: ResourceId,
+    },
+    /// The document doesn't match `scene_schema`, usually because of a misspelled field. Only
+    /// reported when loading in strict mode. The pointer is a json pointer to the offending value.
+    Schema {
+        pointer: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for SceneLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneLoadError::Io(error) => write!(f, "Could not read scene: {}", error),
+            SceneLoadError::Json {
+                line: 0, message, ..
+            } => write!(f, "Could not parse scene: {}", message),
+            SceneLoadError::Json {
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "Could not parse scene at line {} column {}: {}",
+                line, column, message
+            ),
+            SceneLoadError::Format(message) => write!(f, "Invalid scene: {}", message),
+            SceneLoadError::InvalidBase64 { resource, message } => {
+                write!(f, "Invalid base64 data in {}: {}", resource, message)
+            }
+            SceneLoadError::UnresolvedResource {
+                resource,
+                reference,
+                error,
+            } => write!(
+                f,
+                "Could not resolve {} from {:?}: {}",
+                resource, reference, error
+            ),
+            SceneLoadError::TextureSizeMismatch {
+                texture,
+                size,
+                data_length,
+            } => write!(
+                f,
+                "Texture {} is {}x{} but has {} bytes of data instead of {}",
+                texture.raw(),
+                size.width,
+                size.height,
+                data_length,
+                size.width as usize * size.height as usize * 4
+            ),
+            SceneLoadError::UnknownResource { layer, resource } => {
+                write!(f, "Layer {} refers to unknown {}", layer, resource)
+            }
+            SceneLoadError::Schema { pointer, message } if pointer.is_empty() => {
+                write!(f, "Invalid scene: {}", message)
+            }
+            SceneLoadError::Schema { pointer, message } => {
+                write!(f, "Invalid scene at {}: {}", pointer, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneLoadError::Io(error) => Some(error),
+            SceneLoadError::UnresolvedResource { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SceneLoadError {
+    fn from(error: io::Error) -> Self {
+        SceneLoadError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SceneLoadError {
+    fn from(error: serde_json::Error) -> Self {
+        let (line, column) = (error.line(), error.column());
+        // serde_json appends the position to the message, which is stored separately here
+        let message = error.to_string();
+        let position = format!(" at line {} column {}", line, column);
+        let message = match message.strip_suffix(&position) {
+            Some(message) if line != 0 => message.to_string(),
+            _ => message,
+        };
+
+        SceneLoadError::Json {
+            line,
+            column,
+            message,
+        }
+    }
+}
+
+// Resources are parsed in their serialized form first so that references can be resolved and bad
+// base64 can be reported with the resource it belongs to instead of as a generic parse error.
+#[derive(Deserialize)]
+struct RawScene {
+    layers: Vec<Layer>,
+    resources: RawResources,
+}
+
+#[derive(Deserialize)]
+struct RawResources {
+    fonts: HashMap<FontId, ResourceData>,
+    textures: HashMap<TextureId, RawTexture>,
+}
+
+#[derive(Deserialize)]
+struct RawTexture {
+    data: ResourceData,
+    size: Size2<u32>,
+}
+
+#[derive(Deserialize)]
+struct VersionProbe {
+    format_version: Option<u32>,
+}
+
+impl Scene {
+    /// Loads a scene from a file in either the json or binary format. Unlike deserializing the
+    /// scene directly, this checks that textures have the right amount of data and that every
+    /// font and texture referenced by the layers exists, so a scene which loads successfully can
+    /// be rendered without panicking.
+    ///
+    /// Resources stored as references are resolved relative to the directory containing the
+    /// scene file. Use `load_with_resolver` to look them up elsewhere.
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, SceneLoadError> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or(std::path::Path::new("."));
+        Self::load_with_resolver(path, &ResourceDirectory::new(directory))
+    }
+
+    pub fn load_with_resolver(
+        path: impl AsRef<std::path::Path>,
+        resolver: &dyn ResourceResolver,
+    ) -> Result<Self, SceneLoadError> {
+        let bytes = fs::read(path)?;
+        Self::from_bytes_with_resolver(&bytes, resolver)
+    }
+
+    /// Like `load`, but json scenes must match `scene_schema` exactly, so unknown fields are
+    /// reported as errors instead of being ignored.
+    pub fn load_strict(path: impl AsRef<std::path::Path>) -> Result<Self, SceneLoadError> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or(std::path::Path::new("."));
+        let bytes = fs::read(path)?;
+        Self::parse(&bytes, &ResourceDirectory::new(directory), true)
+    }
+
+    /// Parses a scene in either the json or binary format. See `load` for details. Scenes parsed
+    /// this way must store their resources inline.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SceneLoadError> {
+        Self::from_bytes_with_resolver(bytes, &NoResourceResolver)
+    }
+
+    pub fn from_bytes_with_resolver(
+        bytes: &[u8],
+        resolver: &dyn ResourceResolver,
+    ) -> Result<Self, SceneLoadError> {
+        Self::parse(bytes, resolver, false)
+    }
+
+    /// Like `from_bytes`, but rejects json scenes which don't match `scene_schema`. See
+    /// `load_strict`.
+    pub fn from_bytes_strict(bytes: &[u8]) -> Result<Self, SceneLoadError> {
+        Self::parse(bytes, &NoResourceResolver, true)
+    }
+
+    fn parse(
+        bytes: &[u8],
+        resolver: &dyn ResourceResolver,
+        strict: bool,
+    ) -> Result<Self, SceneLoadError> {
+        let scene = if is_binary_scene(bytes) {
+            Self::read_binary(bytes).map_err(|error| SceneLoadError::Format(error.to_string()))?
+        } else {
+            Self::from_json(bytes, resolver, strict)?
+        };
+
+        scene.check_resources()?;
+        Ok(scene)
+    }
+
+    fn from_json(
+        json: &[u8],
+        resolver: &dyn ResourceResolver,
+        strict: bool,
+    ) -> Result<Self, SceneLoadError> {
+        // Current documents are parsed straight from the text so that type errors keep their
+        // position. Older ones, and any checked against the schema, have to go through a Value
+        // first.
+        let probe: VersionProbe = serde_json::from_slice(json)?;
+        let scene: RawScene = if probe.format_version == Some(SCENE_FORMAT_VERSION) && !strict {
+            serde_json::from_slice(json)?
+        } else {
+            let mut document: Value = serde_json::from_slice(json)?;
+            migrate_scene(&mut document).map_err(SceneLoadError::Format)?;
+            if strict {
+                check_scene_schema(&document)?;
+            }
+            serde_json::from_value(document)?
+        };
+
+        let mut resources = Resources::default();
+        for (id, data) in scene.resources.fonts {
+            let data = resolve_data(ResourceId::Font(id), data, resolver)?;
+            resources.fonts.insert(
+                id,
+                Font {
+                    data: Arc::new(data),
+                },
+            );
+        }
+        for (id, texture) in scene.resources.textures {
+            let data = resolve_data(ResourceId::Texture(id), texture.data, resolver)?;
+            resources.textures.insert(
+                id,
+                Texture {
+                    data,
+                    size: texture.size,
+                },
+            );
+        }
+
+        Ok(Self {
+            layers: scene.layers,
+            resources,
+        })
+    }
+
+    fn check_resources(&self) -> Result<(), SceneLoadError> {
+        for (id, texture) in &self.resources.textures {
+            let size = texture.size;
+            if texture.data.len() != size.width as usize * size.height as usize * 4 {
+                return Err(SceneLoadError::TextureSizeMismatch {
+                    texture: *id,
+                    size,
+                    data_length: texture.data.len(),
+                });
+            }
+        }
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            let unknown_resource = |resource| SceneLoadError::UnknownResource {
+                layer: index,
+                resource,
+            };
+
+            for contents in layer.mask.iter().chain([&layer.contents]) {
+                for batch in contents.primitives.iter() {
+                    for glyph_run in batch.as_glyph_run_vec().into_iter().flatten() {
+                        if !self.resources.fonts.contains_key(&glyph_run.font_id) {
+                            return Err(unknown_resource(ResourceId::Font(glyph_run.font_id)));
+                        }
+                    }
+
+                    for sprite in batch.as_sprite_vec().into_iter().flatten() {
+                        if !self.resources.textures.contains_key(&sprite.texture) {
+                            return Err(unknown_resource(ResourceId::Texture(sprite.texture)));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn resolve_data(
+    resource: ResourceId,
+    data: ResourceData,
+    resolver: &dyn ResourceResolver,
+) -> Result<Vec<u8>, SceneLoadError> {
+    data.resolve(resolver).map_err(|error| match error {
+        ResourceDataError::InvalidBase64(error) => SceneLoadError::InvalidBase64 {
+            resource,
+            message: error.to_string(),
+        },
+        ResourceDataError::Unresolved(reference, error) => SceneLoadError::UnresolvedResource {
+            resource,
+            reference,
+            error,
+        },
+    })
+}
@@ -2,7 +2,9 @@ use glamour::Point2;
 use palette::Srgba;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+use super::LineCap;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(untagged)]
 pub enum PathCommand {
     CubicBezierTo {
@@ -19,12 +21,26 @@ pub enum PathCommand {
     },
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+impl PathCommand {
+    /// The point the command ends at.
+    pub fn to(&self) -> Point2 {
+        match self {
+            PathCommand::CubicBezierTo { to, .. }
+            | PathCommand::QuadraticBezierTo { to, .. }
+            | PathCommand::LineTo { to } => *to,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Path {
     #[serde(default)]
     pub fill: Option<Srgba>,
     #[serde(default)]
     pub stroke: Option<(f32, Srgba)>,
+    /// The caps and joins of the stroke. Strokes without a style have butt caps and miter joins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stroke_style: Option<StrokeStyle>,
     pub start: Point2,
     pub commands: Vec<PathCommand>,
     #[serde(default)]
@@ -36,6 +52,7 @@ impl Path {
         Self {
             fill: Some(fill),
             stroke: None,
+            stroke_style: None,
             start,
             commands: Vec::new(),
             open: false,
@@ -46,6 +63,7 @@ impl Path {
         Self {
             fill: None,
             stroke: Some((width, color)),
+            stroke_style: None,
             start,
             commands: Vec::new(),
             open: false,
@@ -61,6 +79,7 @@ impl Path {
         Self {
             fill: None,
             stroke: Some((width, color)),
+            stroke_style: None,
             start,
             commands: Vec::new(),
             open: true,
@@ -71,6 +90,7 @@ impl Path {
         Self {
             fill: None,
             stroke: None,
+            stroke_style: None,
             start,
             commands: Vec::new(),
             open: false,
@@ -96,6 +116,15 @@ impl Path {
         self
     }
 
+    pub fn set_stroke_style(&mut self, style: StrokeStyle) {
+        self.stroke_style = Some(style);
+    }
+
+    pub fn with_stroke_style(mut self, style: StrokeStyle) -> Self {
+        self.set_stroke_style(style);
+        self
+    }
+
     pub fn add_cubic_bezier_to(&mut self, control1: Point2, control2: Point2, to: Point2) {
         self.commands.push(PathCommand::CubicBezierTo {
             control1,
@@ -128,3 +157,96 @@ impl Path {
         self
     }
 }
+
+/// How the ends and corners of a path stroke are drawn, and the dashes it is split into.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct StrokeStyle {
+    /// The cap at the start of an open path. Closed paths have no caps.
+    #[serde(default)]
+    pub start_cap: LineCap,
+    /// The cap at the end of an open path.
+    #[serde(default)]
+    pub end_cap: LineCap,
+    #[serde(default)]
+    pub join: LineJoin,
+    /// The longest a miter join may be as a multiple of the stroke width before it is replaced
+    /// according to the join. Limits below 1 are treated as 1.
+    #[serde(default = "default_miter_limit")]
+    pub miter_limit: f32,
+    /// Alternating lengths of dashes and gaps measured along the stroke, repeated twice when
+    /// there is an odd number of them like in svg. Each dash gets the start and end caps. Strokes
+    /// are solid when the array is empty, contains negative lengths, or sums to no more than the
+    /// tessellation tolerance of a tenth of a pixel. Past 10000 dashes and gaps the rest of a sub
+    /// path is drawn solid.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dash_array: Vec<f32>,
+    /// How far into the dash pattern the stroke starts.
+    #[serde(default)]
+    pub dash_offset: f32,
+}
+
+impl StrokeStyle {
+    pub const fn new() -> Self {
+        Self {
+            start_cap: LineCap::Butt,
+            end_cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.,
+            dash_array: Vec::new(),
+            dash_offset: 0.,
+        }
+    }
+
+    /// Sets both the start and end cap.
+    pub fn with_cap(self, cap: LineCap) -> Self {
+        self.with_start_cap(cap).with_end_cap(cap)
+    }
+
+    pub fn with_start_cap(mut self, cap: LineCap) -> Self {
+        self.start_cap = cap;
+        self
+    }
+
+    pub fn with_end_cap(mut self, cap: LineCap) -> Self {
+        self.end_cap = cap;
+        self
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+
+    pub fn with_dashes(mut self, dash_array: impl Into<Vec<f32>>, dash_offset: f32) -> Self {
+        self.dash_array = dash_array.into();
+        self.dash_offset = dash_offset;
+        self
+    }
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn default_miter_limit() -> f32 {
+    StrokeStyle::new().miter_limit
+}
+
+/// The shape of the corners where the segments of a stroke meet.
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Extends the edges until they meet, falling back to a bevel past the miter limit.
+    #[default]
+    Miter,
+    /// Extends the edges until they meet, cutting the point off at the miter limit.
+    MiterClip,
+    Round,
+    Bevel,
+}
@@ -1,18 +1,28 @@
 use glam::Vec4;
 use glamour::{AsRaw, Rect};
 use palette::Srgba;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use super::{Gradient, GradientInterpolation, GradientShape, Transform, MAX_GRADIENT_STOPS};
 use crate::default_drawables::InstancedQuad;
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Quad {
     pub region: Rect,
     pub color: Srgba,
     #[serde(default)]
-    pub corner_radius: f32,
+    pub corner_radius: CornerRadii,
     #[serde(default)]
     pub edge_blur: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border: Option<Border>,
+    /// Fills the quad in place of `color` when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gradient: Option<Gradient>,
+    /// Maps the quad from its own coordinates to the layer's. The corners, border and edge blur
+    /// are computed before transforming, so they rotate and scale with the quad.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<Transform>,
 }
 
 impl Quad {
@@ -20,13 +30,17 @@ impl Quad {
         Self {
             region,
             color,
-            corner_radius: 0.0,
+            corner_radius: CornerRadii::default(),
             edge_blur: 0.0,
+            border: None,
+            gradient: None,
+            transform: None,
         }
     }
 
-    pub fn with_corner_radius(mut self, corner_radius: f32) -> Self {
-        self.corner_radius = corner_radius;
+    /// Sets the corner radius. Takes either a single radius for every corner or a `CornerRadii`.
+    pub fn with_corner_radius(mut self, corner_radius: impl Into<CornerRadii>) -> Self {
+        self.corner_radius = corner_radius.into();
         self
     }
 
@@ -35,14 +49,349 @@ impl Quad {
         self
     }
 
-    pub fn to_instanced(&self) -> InstancedQuad {
-        InstancedQuad {
+    /// Draws a border inside the edge of the quad which follows the corner radii. Takes either a
+    /// single width or a `BorderWidths`, and either a single color or a `BorderColors`.
+    pub fn with_border(
+        mut self,
+        widths: impl Into<BorderWidths>,
+        colors: impl Into<BorderColors>,
+    ) -> Self {
+        self.border = Some(Border {
+            widths: widths.into(),
+            colors: colors.into(),
+            dash: None,
+        });
+        self
+    }
+
+    /// Draws the border as dashes or dots instead of a solid line. Has no effect unless the quad
+    /// has a border.
+    pub fn with_border_dash(mut self, dash: BorderDash) -> Self {
+        if let Some(border) = &mut self.border {
+            border.dash = Some(dash);
+        }
+        self
+    }
+
+    /// Fills the quad with a gradient instead of its color.
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+
+    /// The instance data for the quad shader, or None when the gradient has more stops than fit
+    /// in it.
+    pub fn to_instanced(&self) -> Option<InstancedQuad> {
+        if let Some(gradient) = &self.gradient {
+            if gradient.stops.len() > MAX_GRADIENT_STOPS {
+                return None;
+            }
+        }
+
+        let (border_widths, border_colors, border_dash) = match &self.border {
+            Some(border) => (
+                Vec4::from_array(border.widths.to_array()),
+                border
+                    .colors
+                    .to_array()
+                    .map(|color| Vec4::from_array(color.into_linear().into())),
+                border.dash.map_or(Vec4::ZERO, |dash| {
+                    let cap = match dash.cap {
+                        LineCap::Butt => 0.,
+                        LineCap::Round => 1.,
+                        LineCap::Square => 2.,
+                    };
+                    Vec4::new(dash.on, dash.off, dash.phase, cap)
+                }),
+            ),
+            None => (Vec4::ZERO, [Vec4::ZERO; 4], Vec4::ZERO),
+        };
+
+        let mut instance = InstancedQuad {
             top_left: *self.region.origin.as_raw(),
             size: *self.region.size.as_raw(),
             color: Vec4::from_array(self.color.into_linear().into()),
-            corner_radius: self.corner_radius,
+            corner_radii: Vec4::from_array(self.corner_radius.to_array()),
+            border_widths,
+            border_colors,
+            border_dash,
+            transform: self.transform.unwrap_or_default().matrix_columns(),
+            transform_translation: self.transform.unwrap_or_default().translation_vector(),
             edge_blur: self.edge_blur,
             ..Default::default()
+        };
+
+        if let Some(gradient) = &self.gradient {
+            instance.gradient_kind = match gradient.shape {
+                GradientShape::Linear { .. } => 1,
+                GradientShape::Radial { .. } => 2,
+            };
+            instance.gradient_interpolation = match gradient.interpolation {
+                GradientInterpolation::Linear => 0,
+                GradientInterpolation::Perceptual => 1,
+            };
+            instance.gradient_stop_count = gradient.stops.len() as u32;
+            instance.gradient_parameters = Vec4::from_array(gradient.parameters(self.region));
+            for (index, stop) in gradient.stops.iter().enumerate() {
+                instance.gradient_offsets[index] = stop.offset;
+                instance.gradient_colors[index] = pack_srgba(stop.color);
+            }
+        }
+
+        Some(instance)
+    }
+}
+
+// Packs a color into 8 bits per channel with the red channel in the lowest byte, matching wgsl's
+// unpack4x8unorm. The channels stay srgb encoded so the 8 bits are spread evenly to the eye.
+fn pack_srgba(color: Srgba) -> u32 {
+    let channel = |value: f32| (value.clamp(0., 1.) * 255.).round() as u32;
+    channel(color.red)
+        | channel(color.green) << 8
+        | channel(color.blue) << 16
+        | channel(color.alpha) << 24
+}
+
+/// The radius of each corner of a quad, going clockwise from the top left. Serialized as a single
+/// number when every corner is the same, which is also the form older scenes use.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CornerRadii {
+    pub top_left: f32,
+    pub top_right: f32,
+    pub bottom_right: f32,
+    pub bottom_left: f32,
+}
+
+impl CornerRadii {
+    pub fn new(top_left: f32, top_right: f32, bottom_right: f32, bottom_left: f32) -> Self {
+        Self {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+        }
+    }
+
+    pub fn uniform(radius: f32) -> Self {
+        Self::new(radius, radius, radius, radius)
+    }
+
+    /// Returns the radius shared by every corner, if they are all the same.
+    pub fn as_uniform(&self) -> Option<f32> {
+        let [top_left, rest @ ..] = self.to_array();
+        rest.iter()
+            .all(|radius| *radius == top_left)
+            .then_some(top_left)
+    }
+
+    /// The radii in the order top left, top right, bottom right, bottom left.
+    pub fn to_array(&self) -> [f32; 4] {
+        [
+            self.top_left,
+            self.top_right,
+            self.bottom_right,
+            self.bottom_left,
+        ]
+    }
+}
+
+impl From<f32> for CornerRadii {
+    fn from(radius: f32) -> Self {
+        Self::uniform(radius)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializableCornerRadii {
+    #[serde(default)]
+    top_left: f32,
+    #[serde(default)]
+    top_right: f32,
+    #[serde(default)]
+    bottom_right: f32,
+    #[serde(default)]
+    bottom_left: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CornerRadiiForm {
+    Uniform(f32),
+    PerCorner(SerializableCornerRadii),
+}
+
+impl Serialize for CornerRadii {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.as_uniform() {
+            Some(radius) => radius.serialize(serializer),
+            None => SerializableCornerRadii {
+                top_left: self.top_left,
+                top_right: self.top_right,
+                bottom_right: self.bottom_right,
+                bottom_left: self.bottom_left,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CornerRadii {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(match CornerRadiiForm::deserialize(deserializer)? {
+            CornerRadiiForm::Uniform(radius) => Self::uniform(radius),
+            CornerRadiiForm::PerCorner(radii) => Self::new(
+                radii.top_left,
+                radii.top_right,
+                radii.bottom_right,
+                radii.bottom_left,
+            ),
+        })
+    }
+}
+
+/// A border drawn inside the edge of a quad. Where two sides with different colors meet, the
+/// corner is split along the line from the outer to the inner corner.
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Border {
+    pub widths: BorderWidths,
+    pub colors: BorderColors,
+    /// Draws the border as dashes instead of a solid line when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dash: Option<BorderDash>,
+}
+
+/// A repeating pattern of dashes along the middle of a border, starting where the top edge
+/// meets the top left corner and running clockwise. The pattern is stretched slightly so that a
+/// whole number of dashes fits around the quad without a seam.
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct BorderDash {
+    /// The length of each dash, not counting its caps.
+    pub on: f32,
+    /// The length of the gap after each dash. Round and square caps extend into the gap.
+    pub off: f32,
+    /// How far along the border the pattern starts. Animating it moves the dashes around the
+    /// quad.
+    #[serde(default)]
+    pub phase: f32,
+    #[serde(default)]
+    pub cap: LineCap,
+}
+
+impl BorderDash {
+    pub fn new(on: f32, off: f32) -> Self {
+        Self {
+            on,
+            off,
+            phase: 0.,
+            cap: LineCap::Butt,
         }
     }
+
+    /// Round dots as wide as the border, `spacing` apart from center to center.
+    pub fn dotted(spacing: f32) -> Self {
+        Self::new(0., spacing).with_cap(LineCap::Round)
+    }
+
+    pub fn with_phase(mut self, phase: f32) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+}
+
+/// The shape of the ends of a dash or line.
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum LineCap {
+    /// Ends exactly at the end point.
+    #[default]
+    Butt,
+    /// Ends in a half circle around the end point.
+    Round,
+    /// Ends in a square around the end point, extending past it by half the width.
+    Square,
+}
+
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct BorderWidths {
+    #[serde(default)]
+    pub top: f32,
+    #[serde(default)]
+    pub right: f32,
+    #[serde(default)]
+    pub bottom: f32,
+    #[serde(default)]
+    pub left: f32,
+}
+
+impl BorderWidths {
+    pub fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// The widths in the order top, right, bottom, left.
+    pub fn to_array(&self) -> [f32; 4] {
+        [self.top, self.right, self.bottom, self.left]
+    }
+}
+
+impl From<f32> for BorderWidths {
+    fn from(width: f32) -> Self {
+        Self::new(width, width, width, width)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct BorderColors {
+    pub top: Srgba,
+    pub right: Srgba,
+    pub bottom: Srgba,
+    pub left: Srgba,
+}
+
+impl BorderColors {
+    pub fn new(top: Srgba, right: Srgba, bottom: Srgba, left: Srgba) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// Returns the color shared by every side, if they are all the same.
+    pub fn as_uniform(&self) -> Option<Srgba> {
+        let [top, rest @ ..] = self.to_array();
+        rest.iter().all(|color| *color == top).then_some(top)
+    }
+
+    /// The colors in the order top, right, bottom, left.
+    pub fn to_array(&self) -> [Srgba; 4] {
+        [self.top, self.right, self.bottom, self.left]
+    }
+}
+
+impl From<Srgba> for BorderColors {
+    fn from(color: Srgba) -> Self {
+        Self::new(color, color, color, color)
+    }
 }
@@ -0,0 +1,186 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    time::{Duration, Instant},
+};
+
+use super::{
+    binary::{decode_layers, invalid_data, BinaryDecoder, BinaryEncoder},
+    FontId, Layer, Resources, Scene, SceneLoadError, TextureId,
+};
+
+/// Magic bytes at the start of every scene recording.
+pub const SCENE_RECORDING_MAGIC: &[u8; 8] = b"VIDEREC\0";
+
+/// Appends scenes to a recording along with the time they were recorded at. Recordings use the
+/// binary scene encoding for each frame, but only write each font and texture the first time a
+/// frame contains it, so long sessions with the same fonts stay small.
+///
+/// Attach one to a `WinitRenderer` with `start_recording` to record every drawn frame.
+pub struct SceneRecorder {
+    writer: Box<dyn Write + Send>,
+    start: Instant,
+    written_fonts: HashSet<FontId>,
+    written_textures: HashSet<TextureId>,
+}
+
+impl SceneRecorder {
+    pub fn create(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn new(writer: impl Write + Send + 'static) -> io::Result<Self> {
+        let mut writer: Box<dyn Write + Send> = Box::new(writer);
+        let mut encoder = BinaryEncoder::default();
+        encoder.write_header(SCENE_RECORDING_MAGIC);
+        writer.write_all(&encoder.bytes)?;
+
+        Ok(Self {
+            writer,
+            start: Instant::now(),
+            written_fonts: HashSet::new(),
+            written_textures: HashSet::new(),
+        })
+    }
+
+    /// Records the scene with the time elapsed since the recorder was created.
+    pub fn record(&mut self, scene: &Scene) -> io::Result<()> {
+        self.record_at(scene, self.start.elapsed())
+    }
+
+    /// Records the scene with an explicit timestamp relative to the start of the recording.
+    pub fn record_at(&mut self, scene: &Scene, timestamp: Duration) -> io::Result<()> {
+        let mut encoder = BinaryEncoder::default();
+
+        let new_fonts: Vec<_> = scene
+            .resources
+            .fonts
+            .iter()
+            .filter(|(id, _)| !self.written_fonts.contains(id))
+            .collect();
+        encoder.write_fonts(new_fonts.iter().copied());
+        let new_textures: Vec<_> = scene
+            .resources
+            .textures
+            .iter()
+            .filter(|(id, _)| !self.written_textures.contains(id))
+            .collect();
+        encoder.write_textures(new_textures.iter().copied());
+        encoder.write_value(&serde_json::to_value(&scene.layers).map_err(invalid_data)?);
+
+        self.writer
+            .write_all(&(timestamp.as_micros() as u64).to_le_bytes())?;
+        self.writer
+            .write_all(&(encoder.bytes.len() as u64).to_le_bytes())?;
+        self.writer.write_all(&encoder.bytes)?;
+
+        self.written_fonts
+            .extend(new_fonts.into_iter().map(|(id, _)| *id));
+        self.written_textures
+            .extend(new_textures.into_iter().map(|(id, _)| *id));
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RecordedFrame {
+    /// Time since the start of the recording.
+    pub timestamp: Duration,
+    pub layers: Vec<Layer>,
+}
+
+/// A recording read back in full. The resources of every frame are collected in `resources`, so
+/// a frame is drawn by pairing its layers with them.
+#[derive(Clone, Debug, Default)]
+pub struct SceneRecording {
+    pub frames: Vec<RecordedFrame>,
+    pub resources: Resources,
+}
+
+impl SceneRecording {
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, SceneLoadError> {
+        let file = File::open(path)?;
+        Self::read(file).map_err(|error| match error.kind() {
+            io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => {
+                SceneLoadError::Format(error.to_string())
+            }
+            _ => SceneLoadError::Io(error),
+        })
+    }
+
+    /// Reads a recording written by a `SceneRecorder`. A frame cut off part way through, as left
+    /// behind when the recording process exits without flushing, ends the recording rather than
+    /// failing it.
+    pub fn read(mut reader: impl Read) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        let mut decoder = BinaryDecoder::new(&bytes);
+        let format_version = decoder.read_header(SCENE_RECORDING_MAGIC)?;
+
+        let mut recording = SceneRecording::default();
+        while !decoder.is_finished() {
+            let Some((timestamp, frame)) = read_frame(&mut decoder) else {
+                break;
+            };
+
+            let mut frame = BinaryDecoder::new(frame);
+            frame.read_fonts(&mut recording.resources)?;
+            frame.read_textures(&mut recording.resources)?;
+            let layers = decode_layers(frame.read_value()?, format_version)?;
+            if !frame.is_finished() {
+                return Err(invalid_data("Trailing bytes after recorded frame"));
+            }
+
+            recording.frames.push(RecordedFrame {
+                timestamp: Duration::from_micros(timestamp),
+                layers,
+            });
+        }
+
+        Ok(recording)
+    }
+
+    /// Builds the full scene for the frame at the given index. This copies every resource in the
+    /// recording, so use `load_frame` when drawing frame after frame.
+    pub fn scene(&self, index: usize) -> Scene {
+        Scene {
+            layers: self.frames[index].layers.clone(),
+            resources: self.resources.clone(),
+        }
+    }
+
+    /// Replaces the layers of the scene with those of the frame at the given index and adds any
+    /// resources the scene doesn't have yet. Reusing one scene for a whole replay copies each
+    /// resource out of the recording only once.
+    pub fn load_frame(&self, index: usize, scene: &mut Scene) {
+        scene.layers.clone_from(&self.frames[index].layers);
+        for (id, font) in &self.resources.fonts {
+            scene
+                .resources
+                .fonts
+                .entry(*id)
+                .or_insert_with(|| font.clone());
+        }
+        for (id, texture) in &self.resources.textures {
+            scene
+                .resources
+                .textures
+                .entry(*id)
+                .or_insert_with(|| texture.clone());
+        }
+    }
+}
+
+fn read_frame<'a>(decoder: &mut BinaryDecoder<'a>) -> Option<(u64, &'a [u8])> {
+    let timestamp = decoder.read_u64().ok()?;
+    let length = decoder.read_u64().ok()?;
+    let frame = decoder.take(usize::try_from(length).ok()?).ok()?;
+    Some((timestamp, frame))
+}
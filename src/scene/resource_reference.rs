@@ -0,0 +1,219 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::{Component, PathBuf},
+};
+
+use base64::prelude::*;
+use glamour::Size2;
+use serde::{Deserialize, Serialize};
+
+use super::{FontId, Layer, Scene, TextureId, SCENE_FORMAT_VERSION};
+
+/// Refers to resource data stored outside of the scene file. References are only resolved when
+/// loading through `Scene::load` and the other `SceneLoadError` returning constructors.
+/// Deserializing a `Scene` directly with serde expects inline data, and the binary format always
+/// stores the resource bytes themselves.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceReference {
+    /// A file containing the raw resource bytes. Paths are resolved by the resolver, usually
+    /// relative to the scene file. `ResourceDirectory` rejects absolute paths and paths with
+    /// `..` components so a scene can't read files outside of its directory.
+    Path(PathBuf),
+    /// The `content_hash` of the resource bytes, looked up in a shared resource store.
+    Hash(String),
+}
+
+/// The serialized form of font and texture data in a scene file. Either the base64 encoded bytes
+/// or a reference to them.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ResourceData {
+    Inline(String),
+    Reference(ResourceReference),
+}
+
+/// Looks up the bytes of resources which are referenced rather than stored inline in a scene.
+pub trait ResourceResolver {
+    fn resolve(&self, reference: &ResourceReference) -> io::Result<Vec<u8>>;
+}
+
+/// A resolver for scenes loaded without any way to find external resources. Fails for every
+/// reference.
+pub struct NoResourceResolver;
+
+impl ResourceResolver for NoResourceResolver {
+    fn resolve(&self, reference: &ResourceReference) -> io::Result<Vec<u8>> {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No resolver available for {:?}", reference),
+        ))
+    }
+}
+
+/// A directory of resources shared between scene files. Path references are resolved relative to
+/// the directory and hash references are looked up as files named after the hash, which lets many
+/// scene dumps share a single copy of each font and texture.
+#[derive(Clone, Debug)]
+pub struct ResourceDirectory {
+    pub root: PathBuf,
+}
+
+impl ResourceDirectory {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Stores the given bytes in the directory if they aren't there already and returns a hash
+    /// reference to them.
+    pub fn store(&self, data: &[u8]) -> io::Result<ResourceReference> {
+        let hash = format!("{:016x}", content_hash(data));
+        let path = self.root.join(&hash);
+        if !path.exists() {
+            fs::create_dir_all(&self.root)?;
+            fs::write(path, data)?;
+        }
+
+        Ok(ResourceReference::Hash(hash))
+    }
+}
+
+impl ResourceResolver for ResourceDirectory {
+    fn resolve(&self, reference: &ResourceReference) -> io::Result<Vec<u8>> {
+        match reference {
+            ResourceReference::Path(path) => {
+                let inside_root = path
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+                if !inside_root {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        format!(
+                            "Resource path {:?} is outside of the resource directory",
+                            path
+                        ),
+                    ));
+                }
+
+                fs::read(self.root.join(path))
+            }
+            ResourceReference::Hash(hash) => {
+                if !hash.chars().all(|character| character.is_ascii_hexdigit()) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Resource hash {:?} is not hexadecimal", hash),
+                    ));
+                }
+
+                let data = fs::read(self.root.join(hash))?;
+                if format!("{:016x}", content_hash(&data)) != *hash {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Resource {} does not match its hash", hash),
+                    ));
+                }
+
+                Ok(data)
+            }
+        }
+    }
+}
+
+/// A stable 64 bit FNV-1a hash of the given bytes. Unlike the std hashers this is the same across
+/// runs, platforms and versions, so it can be stored in scene files.
+pub fn content_hash(data: &[u8]) -> u64 {
+    content_hash_parts(&[data])
+}
+
+/// The `content_hash` of the given slices joined together, without copying them into one buffer.
+pub fn content_hash_parts(parts: &[&[u8]]) -> u64 {
+    parts
+        .iter()
+        .flat_map(|part| part.iter())
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+#[derive(Serialize)]
+struct SharedScene<'a> {
+    format_version: u32,
+    layers: &'a [Layer],
+    resources: SharedResources,
+}
+
+#[derive(Serialize)]
+struct SharedResources {
+    fonts: HashMap<FontId, ResourceData>,
+    textures: HashMap<TextureId, SharedTexture>,
+}
+
+#[derive(Serialize)]
+struct SharedTexture {
+    data: ResourceData,
+    size: Size2<u32>,
+}
+
+impl Scene {
+    /// Writes the scene as json like serializing it would, except that every font and texture is
+    /// stored in the given directory and only referenced by hash. Scenes written this way are
+    /// loaded with `Scene::load_with_resolver` and the same directory.
+    pub fn write_json_with_shared_resources(
+        &self,
+        writer: impl Write,
+        directory: &ResourceDirectory,
+    ) -> io::Result<()> {
+        let mut resources = SharedResources {
+            fonts: HashMap::new(),
+            textures: HashMap::new(),
+        };
+        for (id, font) in &self.resources.fonts {
+            let reference = directory.store(&font.data)?;
+            resources
+                .fonts
+                .insert(*id, ResourceData::Reference(reference));
+        }
+        for (id, texture) in &self.resources.textures {
+            let reference = directory.store(&texture.data)?;
+            resources.textures.insert(
+                *id,
+                SharedTexture {
+                    data: ResourceData::Reference(reference),
+                    size: texture.size,
+                },
+            );
+        }
+
+        let scene = SharedScene {
+            format_version: SCENE_FORMAT_VERSION,
+            layers: &self.layers,
+            resources,
+        };
+        serde_json::to_writer(writer, &scene).map_err(io::Error::from)
+    }
+}
+
+impl ResourceData {
+    /// Returns the bytes of the resource, decoding inline data and resolving references with the
+    /// given resolver.
+    pub(crate) fn resolve(
+        self,
+        resolver: &dyn ResourceResolver,
+    ) -> Result<Vec<u8>, ResourceDataError> {
+        match self {
+            ResourceData::Inline(data) => BASE64_STANDARD
+                .decode(data)
+                .map_err(ResourceDataError::InvalidBase64),
+            ResourceData::Reference(reference) => resolver
+                .resolve(&reference)
+                .map_err(|error| ResourceDataError::Unresolved(reference, error)),
+        }
+    }
+}
+
+pub(crate) enum ResourceDataError {
+    InvalidBase64(base64::DecodeError),
+    Unresolved(ResourceReference, io::Error),
+}
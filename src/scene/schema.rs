@@ -0,0 +1,670 @@
+use serde_json::{json, Map, Value};
+
+use super::SceneLoadError;
+
+/// Builds a JSON Schema (draft 2020-12) describing the json scene format for the current
+/// `SCENE_FORMAT_VERSION`. Every object in the schema disallows additional properties, so editors
+/// using it flag misspelled fields which deserialization would otherwise ignore.
+pub fn scene_schema() -> Value {
+    let number = json!({ "type": "number" });
+    let unsigned = json!({ "type": "integer", "minimum": 0 });
+    let point = object(&[("x", &number), ("y", &number)], &["x", "y"]);
+    let size = object(
+        &[("width", &number), ("height", &number)],
+        &["width", "height"],
+    );
+    let batch = |name: &str, primitive: &str| {
+        let primitives = json!({ "type": "array", "items": reference(primitive) });
+        object(&[(name, &primitives)], &[name])
+    };
+
+    let definitions = [
+        (
+            "Layer",
+            json!({
+                "type": "object",
+                "properties": {
+                    "clip": { "oneOf": [reference("ClipRect"), { "type": "null" }] },
+                    "mask": { "oneOf": [reference("LayerContents"), { "type": "null" }] },
+                    "primitives": primitive_batches(),
+                },
+                "additionalProperties": false,
+            }),
+        ),
+        (
+            "LayerContents",
+            object(&[("primitives", &primitive_batches())], &[]),
+        ),
+        (
+            "MutablePrimitiveBatch",
+            json!({
+                "oneOf": [
+                    batch("Blurs", "Blur"),
+                    batch("Quads", "Quad"),
+                    batch("GlyphRuns", "GlyphRun"),
+                    batch("Paths", "Path"),
+                    batch("Sprites", "Sprite"),
+                    batch("Shadows", "Shadow"),
+                    batch("Ellipses", "Ellipse"),
+                    batch("LineSegments", "LineSegment"),
+                    batch("Undercurls", "Undercurl"),
+                ],
+            }),
+        ),
+        (
+            "Blur",
+            object(
+                &[
+                    ("top_left", &reference("Point")),
+                    ("size", &reference("Size")),
+                    ("color", &reference("Color")),
+                    ("corner_radius", &number),
+                    ("blur", &number),
+                ],
+                &["top_left", "size", "color"],
+            ),
+        ),
+        (
+            "Quad",
+            object(
+                &[
+                    ("region", &reference("Rect")),
+                    ("color", &reference("Color")),
+                    ("corner_radius", &reference("CornerRadii")),
+                    ("edge_blur", &number),
+                    ("border", &reference("Border")),
+                    ("gradient", &reference("Gradient")),
+                    ("transform", &reference("Transform")),
+                ],
+                &["region", "color"],
+            ),
+        ),
+        (
+            "GlyphRun",
+            object(
+                &[
+                    ("position", &reference("Point")),
+                    ("font_id", &unsigned),
+                    ("font_index", &unsigned),
+                    ("color", &reference("Color")),
+                    ("size", &number),
+                    (
+                        "normalized_coords",
+                        &json!({ "type": "array", "items": { "type": "integer" } }),
+                    ),
+                    ("synthesis", &reference("Synthesis")),
+                    (
+                        "glyphs",
+                        &json!({ "type": "array", "items": reference("Glyph") }),
+                    ),
+                ],
+                &[
+                    "position",
+                    "font_id",
+                    "font_index",
+                    "color",
+                    "size",
+                    "normalized_coords",
+                    "synthesis",
+                    "glyphs",
+                ],
+            ),
+        ),
+        (
+            "Glyph",
+            object(
+                &[("id", &unsigned), ("offset", &reference("Point"))],
+                &["id", "offset"],
+            ),
+        ),
+        (
+            "Synthesis",
+            object(
+                &[
+                    (
+                        "vars",
+                        &json!({ "type": "array", "items": reference("Setting") }),
+                    ),
+                    ("embolden", &json!({ "type": "boolean" })),
+                    ("skew", &number),
+                ],
+                &["vars", "embolden", "skew"],
+            ),
+        ),
+        (
+            "Setting",
+            object(&[("tag", &unsigned), ("value", &number)], &["tag", "value"]),
+        ),
+        (
+            "Path",
+            object(
+                &[
+                    (
+                        "fill",
+                        &json!({ "oneOf": [reference("Color"), { "type": "null" }] }),
+                    ),
+                    (
+                        "stroke",
+                        &json!({
+                            "oneOf": [
+                                {
+                                    "type": "array",
+                                    "prefixItems": [number.clone(), reference("Color")],
+                                    "items": false,
+                                    "minItems": 2,
+                                },
+                                { "type": "null" },
+                            ],
+                        }),
+                    ),
+                    ("stroke_style", &reference("StrokeStyle")),
+                    ("start", &reference("Point")),
+                    (
+                        "commands",
+                        &json!({ "type": "array", "items": reference("PathCommand") }),
+                    ),
+                    ("open", &json!({ "type": "boolean" })),
+                ],
+                &["start", "commands"],
+            ),
+        ),
+        (
+            "StrokeStyle",
+            object(
+                &[
+                    ("start_cap", &reference("LineCap")),
+                    ("end_cap", &reference("LineCap")),
+                    ("join", &reference("LineJoin")),
+                    ("miter_limit", &number),
+                    (
+                        "dash_array",
+                        &json!({ "type": "array", "items": number.clone() }),
+                    ),
+                    ("dash_offset", &number),
+                ],
+                &[],
+            ),
+        ),
+        (
+            "PathCommand",
+            json!({
+                "oneOf": [
+                    object(
+                        &[
+                            ("control1", &reference("Point")),
+                            ("control2", &reference("Point")),
+                            ("to", &reference("Point")),
+                        ],
+                        &["control1", "control2", "to"],
+                    ),
+                    object(
+                        &[("control", &reference("Point")), ("to", &reference("Point"))],
+                        &["control", "to"],
+                    ),
+                    object(&[("to", &reference("Point"))], &["to"]),
+                ],
+            }),
+        ),
+        (
+            "Sprite",
+            object(
+                &[
+                    ("top_left", &reference("Point")),
+                    ("size", &reference("Size")),
+                    ("color", &reference("Color")),
+                    ("texture", &unsigned),
+                    ("transform", &reference("Transform")),
+                ],
+                &["top_left", "size", "color", "texture"],
+            ),
+        ),
+        (
+            "Shadow",
+            object(
+                &[
+                    ("region", &reference("Rect")),
+                    ("color", &reference("Color")),
+                    ("corner_radius", &reference("CornerRadii")),
+                    ("offset", &reference("Point")),
+                    ("blur", &number),
+                    ("spread", &number),
+                    ("inset", &json!({ "type": "boolean" })),
+                ],
+                &["region", "color"],
+            ),
+        ),
+        (
+            "Ellipse",
+            object(
+                &[
+                    ("center", &reference("Point")),
+                    ("radii", &reference("Point")),
+                    ("color", &reference("Color")),
+                    ("stroke_width", &number),
+                    ("stroke_color", &reference("Color")),
+                ],
+                &["center", "radii", "color"],
+            ),
+        ),
+        (
+            "LineSegment",
+            object(
+                &[
+                    ("start", &reference("Point")),
+                    ("end", &reference("Point")),
+                    ("width", &number),
+                    ("color", &reference("Color")),
+                    ("cap", &reference("LineCap")),
+                ],
+                &["start", "end", "width", "color"],
+            ),
+        ),
+        (
+            "Undercurl",
+            object(
+                &[
+                    ("start", &reference("Point")),
+                    ("length", &number),
+                    ("thickness", &number),
+                    ("wavelength", &number),
+                    ("amplitude", &number),
+                    ("color", &reference("Color")),
+                ],
+                &[
+                    "start",
+                    "length",
+                    "thickness",
+                    "wavelength",
+                    "amplitude",
+                    "color",
+                ],
+            ),
+        ),
+        (
+            "Resources",
+            object(
+                &[
+                    (
+                        "fonts",
+                        &json!({
+                            "type": "object",
+                            "additionalProperties": reference("ResourceData"),
+                        }),
+                    ),
+                    (
+                        "textures",
+                        &json!({
+                            "type": "object",
+                            "additionalProperties": reference("Texture"),
+                        }),
+                    ),
+                ],
+                &["fonts", "textures"],
+            ),
+        ),
+        (
+            "Texture",
+            object(
+                &[
+                    ("data", &reference("ResourceData")),
+                    ("size", &reference("TextureSize")),
+                ],
+                &["data", "size"],
+            ),
+        ),
+        (
+            "ResourceData",
+            json!({
+                "oneOf": [
+                    { "type": "string", "contentEncoding": "base64" },
+                    object(&[("path", &json!({ "type": "string" }))], &["path"]),
+                    object(&[("hash", &json!({ "type": "string" }))], &["hash"]),
+                ],
+            }),
+        ),
+        (
+            "CornerRadii",
+            json!({
+                "oneOf": [
+                    number.clone(),
+                    object(
+                        &[
+                            ("top_left", &number),
+                            ("top_right", &number),
+                            ("bottom_right", &number),
+                            ("bottom_left", &number),
+                        ],
+                        &[],
+                    ),
+                ],
+            }),
+        ),
+        (
+            "Border",
+            object(
+                &[
+                    (
+                        "widths",
+                        &object(
+                            &[
+                                ("top", &number),
+                                ("right", &number),
+                                ("bottom", &number),
+                                ("left", &number),
+                            ],
+                            &[],
+                        ),
+                    ),
+                    (
+                        "colors",
+                        &object(
+                            &[
+                                ("top", &reference("Color")),
+                                ("right", &reference("Color")),
+                                ("bottom", &reference("Color")),
+                                ("left", &reference("Color")),
+                            ],
+                            &["top", "right", "bottom", "left"],
+                        ),
+                    ),
+                    (
+                        "dash",
+                        &object(
+                            &[
+                                ("on", &number),
+                                ("off", &number),
+                                ("phase", &number),
+                                ("cap", &reference("LineCap")),
+                            ],
+                            &["on", "off"],
+                        ),
+                    ),
+                ],
+                &["widths", "colors"],
+            ),
+        ),
+        (
+            "Gradient",
+            object(
+                &[
+                    (
+                        "shape",
+                        &json!({
+                            "oneOf": [
+                                object(
+                                    &[("Linear", &object(&[("angle", &number)], &["angle"]))],
+                                    &["Linear"],
+                                ),
+                                object(
+                                    &[(
+                                        "Radial",
+                                        &object(
+                                            &[("center", &reference("Point")), ("radius", &number)],
+                                            &["center", "radius"],
+                                        ),
+                                    )],
+                                    &["Radial"],
+                                ),
+                            ],
+                        }),
+                    ),
+                    (
+                        "stops",
+                        &json!({ "type": "array", "items": reference("GradientStop") }),
+                    ),
+                    (
+                        "interpolation",
+                        &json!({ "type": "string", "enum": ["Linear", "Perceptual"] }),
+                    ),
+                ],
+                &["shape", "stops"],
+            ),
+        ),
+        (
+            "GradientStop",
+            object(
+                &[("offset", &number), ("color", &reference("Color"))],
+                &["offset", "color"],
+            ),
+        ),
+        (
+            "LineCap",
+            json!({ "type": "string", "enum": ["Butt", "Round", "Square"] }),
+        ),
+        (
+            "LineJoin",
+            json!({ "type": "string", "enum": ["Miter", "MiterClip", "Round", "Bevel"] }),
+        ),
+        (
+            "Transform",
+            object(
+                &[
+                    ("a", &number),
+                    ("b", &number),
+                    ("c", &number),
+                    ("d", &number),
+                    ("e", &number),
+                    ("f", &number),
+                ],
+                &["a", "b", "c", "d", "e", "f"],
+            ),
+        ),
+        ("Point", point),
+        ("Size", size),
+        (
+            "Rect",
+            object(
+                &[
+                    ("origin", &reference("Point")),
+                    ("size", &reference("Size")),
+                ],
+                &["origin", "size"],
+            ),
+        ),
+        (
+            "ClipRect",
+            object(
+                &[
+                    (
+                        "origin",
+                        &object(&[("x", &unsigned), ("y", &unsigned)], &["x", "y"]),
+                    ),
+                    ("size", &reference("TextureSize")),
+                ],
+                &["origin", "size"],
+            ),
+        ),
+        (
+            "TextureSize",
+            object(
+                &[("width", &unsigned), ("height", &unsigned)],
+                &["width", "height"],
+            ),
+        ),
+        (
+            "Color",
+            object(
+                &[
+                    ("red", &number),
+                    ("green", &number),
+                    ("blue", &number),
+                    ("alpha", &number),
+                ],
+                &["red", "green", "blue", "alpha"],
+            ),
+        ),
+    ];
+
+    let mut schema = object(
+        &[
+            ("format_version", &unsigned),
+            (
+                "layers",
+                &json!({ "type": "array", "items": reference("Layer") }),
+            ),
+            ("resources", &reference("Resources")),
+        ],
+        &["layers", "resources"],
+    );
+    schema["$schema"] = json!("https://json-schema.org/draft/2020-12/schema");
+    schema["title"] = json!("Scene");
+    schema["$defs"] = Value::Object(
+        definitions
+            .into_iter()
+            .map(|(name, definition)| (name.to_string(), definition))
+            .collect(),
+    );
+    schema
+}
+
+/// Checks a scene document against `scene_schema`, reporting the first value which doesn't match.
+/// Only the parts of JSON Schema used by the scene schema are supported.
+pub fn check_scene_schema(document: &Value) -> Result<(), SceneLoadError> {
+    let schema = scene_schema();
+    check(&schema, &schema, document, "")
+        .map_err(|(pointer, message)| SceneLoadError::Schema { pointer, message })
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{}", name) })
+}
+
+fn object(properties: &[(&str, &Value)], required: &[&str]) -> Value {
+    let properties: Map<String, Value> = properties
+        .iter()
+        .map(|(name, schema)| (name.to_string(), (*schema).clone()))
+        .collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn primitive_batches() -> Value {
+    json!({ "type": "array", "items": reference("MutablePrimitiveBatch") })
+}
+
+fn check(
+    root: &Value,
+    schema: &Value,
+    value: &Value,
+    pointer: &str,
+) -> Result<(), (String, String)> {
+    let error = |message: String| Err((pointer.to_string(), message));
+
+    match schema {
+        Value::Bool(true) => return Ok(()),
+        Value::Bool(false) => return error("Unexpected value".to_string()),
+        _ => {}
+    }
+
+    if let Some(Value::String(reference)) = schema.get("$ref") {
+        let name = reference.trim_start_matches("#/$defs/");
+        return check(root, &root["$defs"][name], value, pointer);
+    }
+
+    if let Some(Value::Array(options)) = schema.get("oneOf") {
+        // Report the problem from the option which got furthest into the value, which is usually
+        // the one the author intended
+        let mut deepest: Option<(String, String)> = None;
+        let mut matches = 0;
+        for option in options {
+            match check(root, option, value, pointer) {
+                Ok(()) => matches += 1,
+                Err(problem) => {
+                    let deeper = match &deepest {
+                        Some(deepest) => problem.0.len() > deepest.0.len(),
+                        None => true,
+                    };
+                    if deeper {
+                        deepest = Some(problem);
+                    }
+                }
+            }
+        }
+        return match matches {
+            0 => Err(deepest.unwrap_or_else(|| (pointer.to_string(), "No options".to_string()))),
+            1 => Ok(()),
+            _ => error("Matches more than one option".to_string()),
+        };
+    }
+
+    if let Some(Value::String(expected)) = schema.get("type") {
+        let matches = match expected.as_str() {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            "number" => value.is_number(),
+            "integer" => value.is_i64() || value.is_u64(),
+            _ => true,
+        };
+        if !matches {
+            return error(format!("Expected {}", expected));
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            return error(format!("Expected one of {}", Value::Array(allowed.clone())));
+        }
+    }
+
+    if let (Some(minimum), Some(number)) = (
+        schema.get("minimum").and_then(Value::as_f64),
+        value.as_f64(),
+    ) {
+        if number < minimum {
+            return error(format!("Expected at least {}", minimum));
+        }
+    }
+
+    if let Value::Object(fields) = value {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !fields.contains_key(name) {
+                    return error(format!("Missing field `{}`", name));
+                }
+            }
+        }
+
+        for (name, field) in fields {
+            let field_pointer = format!("{}/{}", pointer, name);
+            match properties.and_then(|properties| properties.get(name)) {
+                Some(property) => check(root, property, field, &field_pointer)?,
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        return error(format!("Unknown field `{}`", name));
+                    }
+                    Some(additional) => check(root, additional, field, &field_pointer)?,
+                    None => {}
+                },
+            }
+        }
+    }
+
+    if let Value::Array(items) = value {
+        let prefix = schema
+            .get("prefixItems")
+            .and_then(Value::as_array)
+            .map_or(&[][..], Vec::as_slice);
+        if let Some(minimum) = schema.get("minItems").and_then(Value::as_u64) {
+            if (items.len() as u64) < minimum {
+                return error(format!("Expected at least {} items", minimum));
+            }
+        }
+
+        for (index, item) in items.iter().enumerate() {
+            let item_pointer = format!("{}/{}", pointer, index);
+            if let Some(item_schema) = prefix.get(index).or_else(|| schema.get("items")) {
+                check(root, item_schema, item, &item_pointer)?;
+            }
+        }
+    }
+
+    Ok(())
+}
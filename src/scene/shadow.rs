@@ -0,0 +1,117 @@
+use glam::Vec4;
+use glamour::{AsRaw, Point2, Rect, Size2, Vector2};
+use palette::Srgba;
+use serde::{Deserialize, Serialize};
+
+use super::CornerRadii;
+use crate::default_drawables::InstancedShadow;
+
+/// A gaussian blurred shadow of a rounded rect, like a css `box-shadow`. Outset shadows are drawn
+/// only outside of `region` so they can sit under translucent boxes, and inset shadows only
+/// inside of it.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Shadow {
+    /// The box casting the shadow.
+    pub region: Rect,
+    pub color: Srgba,
+    #[serde(default)]
+    pub corner_radius: CornerRadii,
+    #[serde(default)]
+    pub offset: Vector2,
+    /// The blur radius. The shadow fades out over roughly this distance on either side of its
+    /// edge, and the gaussian's standard deviation is half of it.
+    #[serde(default)]
+    pub blur: f32,
+    /// How far the shadow is grown past the box, or shrunk into it for inset shadows.
+    #[serde(default)]
+    pub spread: f32,
+    #[serde(default)]
+    pub inset: bool,
+}
+
+impl Shadow {
+    pub fn new(region: Rect, color: Srgba, blur: f32) -> Self {
+        Self {
+            region,
+            color,
+            corner_radius: CornerRadii::default(),
+            offset: Vector2::new(0., 0.),
+            blur,
+            spread: 0.0,
+            inset: false,
+        }
+    }
+
+    /// Sets the corner radius of the box. Takes either a single radius for every corner or a
+    /// `CornerRadii`.
+    pub fn with_corner_radius(mut self, corner_radius: impl Into<CornerRadii>) -> Self {
+        self.corner_radius = corner_radius.into();
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Vector2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_blur(mut self, blur: f32) -> Self {
+        self.blur = blur;
+        self
+    }
+
+    pub fn with_spread(mut self, spread: f32) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    pub fn with_inset(mut self, inset: bool) -> Self {
+        self.inset = inset;
+        self
+    }
+
+    /// The rounded rect which is blurred to make the shadow, which is the box moved by the offset
+    /// and grown by the spread, or shrunk by it for inset shadows.
+    pub fn shape(&self) -> (Rect, CornerRadii) {
+        let spread = if self.inset {
+            -self.spread
+        } else {
+            self.spread
+        };
+        let origin = Point2::new(
+            self.region.origin.x + self.offset.x - spread,
+            self.region.origin.y + self.offset.y - spread,
+        );
+        let size = Size2::new(
+            (self.region.size.width + spread * 2.).max(0.),
+            (self.region.size.height + spread * 2.).max(0.),
+        );
+        let radii = self.corner_radius.to_array().map(|radius| {
+            // Square corners stay square like in css
+            if radius > 0. {
+                (radius + spread).max(0.)
+            } else {
+                0.
+            }
+        });
+        (
+            Rect::new(origin, size),
+            CornerRadii::new(radii[0], radii[1], radii[2], radii[3]),
+        )
+    }
+
+    pub fn to_instanced(&self) -> InstancedShadow {
+        let (shape, shape_radii) = self.shape();
+        InstancedShadow {
+            color: Vec4::from_array(self.color.into_linear().into()),
+            box_corner_radii: Vec4::from_array(self.corner_radius.to_array()),
+            shadow_corner_radii: Vec4::from_array(shape_radii.to_array()),
+            box_top_left: *self.region.origin.as_raw(),
+            box_size: *self.region.size.as_raw(),
+            shadow_top_left: *shape.origin.as_raw(),
+            shadow_size: *shape.size.as_raw(),
+            sigma: self.blur.max(0.) / 2.,
+            inset: self.inset as u32,
+            ..Default::default()
+        }
+    }
+}
@@ -1,7 +1,4 @@
-use std::{
-    fmt::Debug,
-    sync::atomic::{AtomicU64, Ordering},
-};
+use std::fmt::Debug;
 
 use base64::prelude::*;
 use glamour::{Point2, Size2};
@@ -9,15 +6,14 @@ use image::{DynamicImage, GenericImageView};
 use palette::Srgba;
 use serde::{Deserialize, Serialize};
 
-use crate::Resources;
-
-static ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+use super::Transform;
+use crate::{content_hash_parts, Resources};
 
 // Private Sealed trait is used here to ensure that the only two types allowed as generic type
 // arguments to sprite are Texture and TextureId. This way the same type can be used both before
 // the texture is stored in resources with Texture and after with TextureId.
 //
-// Further, since TextureId has a private field, it also can only be constructed inside this file
+// Further, since TextureId has a private field, it also can only be constructed inside this crate
 // ensuring that the only way to add a sprite to a layer is by first adding the texture to the
 // resources. This doesn't prevent a user from adding the texture to a separate scene and then
 // adding the resulting texture id to a scene that doesn't contain the texture id, but its good
@@ -30,12 +26,15 @@ impl SpriteTexture for Texture {}
 impl Sealed for TextureId {}
 impl SpriteTexture for TextureId {}
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Sprite<T: SpriteTexture> {
     pub top_left: Point2,
     pub size: Size2,
     pub color: Srgba,
     pub texture: T,
+    /// Maps the sprite from its own coordinates to the layer's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<Transform>,
 }
 
 impl<T: SpriteTexture> Sprite<T> {
@@ -45,6 +44,7 @@ impl<T: SpriteTexture> Sprite<T> {
             size,
             color: Srgba::new(1., 1., 1., 1.),
             texture,
+            transform: None,
         }
     }
 
@@ -52,6 +52,11 @@ impl<T: SpriteTexture> Sprite<T> {
         self.color = color;
         self
     }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = Some(transform);
+        self
+    }
 }
 
 impl Sprite<Texture> {
@@ -64,6 +69,7 @@ impl Sprite<Texture> {
             size: self.size,
             color: self.color,
             texture: texture_id,
+            transform: self.transform,
         }
     }
 }
@@ -71,13 +77,24 @@ impl Sprite<Texture> {
 #[derive(Copy, Clone, Debug, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct TextureId(u64);
 
+impl TextureId {
+    pub(crate) fn new(id: u64) -> Self {
+        Self(id)
+    }
+
+    pub(crate) fn raw(&self) -> u64 {
+        self.0
+    }
+}
+
 impl Resources {
-    /// Stores a texture in the resources and returns a TextureId. Note: this currently doesn't
-    /// check for duplication, so be careful to not store the same texture multiple times.
+    /// Stores a texture in the resources and returns a TextureId. The id is derived from the
+    /// texture contents and size, so storing an identical texture again returns the existing id
+    /// and ids are stable across runs.
     pub fn store_texture(&mut self, texture: Texture) -> TextureId {
-        let id = ID_COUNTER.fetch_add(1, Ordering::Relaxed);
-        self.textures.insert(TextureId(id), texture);
-        TextureId(id)
+        let id = texture.id();
+        self.textures.entry(id).or_insert(texture);
+        id
     }
 }
 
@@ -88,6 +105,15 @@ pub struct Texture {
 }
 
 impl Texture {
+    /// The content addressed id this texture is stored under in the resources.
+    pub fn id(&self) -> TextureId {
+        TextureId(content_hash_parts(&[
+            &self.size.width.to_le_bytes(),
+            &self.size.height.to_le_bytes(),
+            &self.data,
+        ]))
+    }
+
     pub fn from_image(image: DynamicImage) -> Self {
         let data = image.to_rgba8();
         let (image_width, image_height) = image.dimensions();
@@ -110,7 +136,9 @@ impl<'a> Deserialize<'a> for Texture {
         D: serde::Deserializer<'a>,
     {
         let serializable_texture = SerializableTexture::deserialize(deserializer)?;
-        let data = BASE64_STANDARD.decode(serializable_texture.data).unwrap();
+        let data = BASE64_STANDARD
+            .decode(serializable_texture.data)
+            .map_err(serde::de::Error::custom)?;
         Ok(Self {
             data,
             size: serializable_texture.size,
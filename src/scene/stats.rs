@@ -0,0 +1,285 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::{self, Display},
+    mem::{size_of, size_of_val},
+};
+
+use ordered_float::OrderedFloat;
+
+use super::{FontId, LayerContents, PrimitiveBatch, Scene};
+use crate::default_drawables::{
+    tessellate_paths, InstancedBlur, InstancedEllipse, InstancedGlyph, InstancedLineSegment,
+    InstancedQuad, InstancedShadow, InstancedSprite, InstancedUndercurl,
+};
+
+/// A summary of what a scene contains and roughly how much data drawing it uploads, as returned
+/// by `Scene::stats`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SceneStats {
+    /// Primitive counts across every layer, including masks.
+    pub primitives: PrimitiveCounts,
+    pub layers: Vec<LayerStats>,
+    pub batches: usize,
+    pub shared_batches: usize,
+    pub mutable_batches: usize,
+    /// The number of distinct glyphs drawn with each font and size, sorted by font and size.
+    /// Each of these takes a separate spot in the glyph atlas.
+    pub glyphs: Vec<GlyphStats>,
+    pub font_bytes: usize,
+    pub texture_bytes: usize,
+    pub instance_bytes: InstanceBytes,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrimitiveCounts {
+    pub blurs: usize,
+    pub quads: usize,
+    pub glyph_runs: usize,
+    /// The glyphs in all of the glyph runs.
+    pub glyphs: usize,
+    pub paths: usize,
+    pub sprites: usize,
+    pub shadows: usize,
+    pub ellipses: usize,
+    pub line_segments: usize,
+    pub undercurls: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LayerStats {
+    pub batches: usize,
+    pub primitives: PrimitiveCounts,
+    pub mask_batches: usize,
+    pub mask_primitives: PrimitiveCounts,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphStats {
+    pub font: FontId,
+    pub size: f32,
+    pub unique_glyphs: usize,
+}
+
+/// Estimated bytes each default drawable uploads to the gpu for one frame of the scene. Paths
+/// are tessellated to count their vertices and indices exactly, everything else is one instance
+/// per primitive or glyph.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InstanceBytes {
+    pub blurs: usize,
+    pub quads: usize,
+    pub glyphs: usize,
+    pub paths: usize,
+    pub sprites: usize,
+    pub shadows: usize,
+    pub ellipses: usize,
+    pub line_segments: usize,
+    pub undercurls: usize,
+}
+
+impl InstanceBytes {
+    pub fn total(&self) -> usize {
+        self.blurs
+            + self.quads
+            + self.glyphs
+            + self.paths
+            + self.sprites
+            + self.shadows
+            + self.ellipses
+            + self.line_segments
+            + self.undercurls
+    }
+}
+
+impl PrimitiveCounts {
+    fn add(&mut self, other: &PrimitiveCounts) {
+        self.blurs += other.blurs;
+        self.quads += other.quads;
+        self.glyph_runs += other.glyph_runs;
+        self.glyphs += other.glyphs;
+        self.paths += other.paths;
+        self.sprites += other.sprites;
+        self.shadows += other.shadows;
+        self.ellipses += other.ellipses;
+        self.line_segments += other.line_segments;
+        self.undercurls += other.undercurls;
+    }
+}
+
+impl Scene {
+    /// Collects statistics about the scene for diagnosing slow frames. This tessellates every
+    /// path, so it is about as expensive as preparing the scene for drawing.
+    pub fn stats(&self) -> SceneStats {
+        let mut stats = SceneStats {
+            font_bytes: self
+                .resources
+                .fonts
+                .values()
+                .map(|font| font.data.len())
+                .sum(),
+            texture_bytes: self
+                .resources
+                .textures
+                .values()
+                .map(|texture| texture.data.len())
+                .sum(),
+            ..Default::default()
+        };
+
+        let mut glyphs: BTreeMap<(u64, OrderedFloat<f32>), HashSet<u16>> = BTreeMap::new();
+        for layer in &self.layers {
+            let mut layer_stats = LayerStats::default();
+            if let Some(mask) = &layer.mask {
+                layer_stats.mask_batches = mask.primitives.len();
+                layer_stats.mask_primitives = stats.add_contents(mask, &mut glyphs);
+            }
+            layer_stats.batches = layer.contents.primitives.len();
+            layer_stats.primitives = stats.add_contents(&layer.contents, &mut glyphs);
+            stats.layers.push(layer_stats);
+        }
+
+        stats.glyphs = glyphs
+            .into_iter()
+            .map(|((font, size), ids)| GlyphStats {
+                font: FontId::new(font),
+                size: size.into_inner(),
+                unique_glyphs: ids.len(),
+            })
+            .collect();
+        stats
+    }
+}
+
+impl SceneStats {
+    fn add_contents(
+        &mut self,
+        contents: &LayerContents,
+        glyphs: &mut BTreeMap<(u64, OrderedFloat<f32>), HashSet<u16>>,
+    ) -> PrimitiveCounts {
+        let mut counts = PrimitiveCounts::default();
+        for batch in &contents.primitives {
+            self.batches += 1;
+            match batch {
+                PrimitiveBatch::Shared(_) => self.shared_batches += 1,
+                PrimitiveBatch::Mutable(_) => self.mutable_batches += 1,
+            }
+
+            if let Some(blurs) = batch.as_blur_vec() {
+                counts.blurs += blurs.len();
+                self.instance_bytes.blurs += blurs.len() * size_of::<InstancedBlur>();
+            } else if let Some(quads) = batch.as_quad_vec() {
+                counts.quads += quads.len();
+                self.instance_bytes.quads += quads.len() * size_of::<InstancedQuad>();
+            } else if let Some(glyph_runs) = batch.as_glyph_run_vec() {
+                counts.glyph_runs += glyph_runs.len();
+                for glyph_run in glyph_runs {
+                    counts.glyphs += glyph_run.glyphs.len();
+                    self.instance_bytes.glyphs +=
+                        glyph_run.glyphs.len() * size_of::<InstancedGlyph>();
+                    glyphs
+                        .entry((glyph_run.font_id.raw(), glyph_run.size.into()))
+                        .or_default()
+                        .extend(glyph_run.glyphs.iter().map(|glyph| glyph.id));
+                }
+            } else if let Some(paths) = batch.as_path_vec() {
+                counts.paths += paths.len();
+                let geometry = tessellate_paths(paths);
+                self.instance_bytes.paths += size_of_val(geometry.vertices.as_slice())
+                    + size_of_val(geometry.indices.as_slice());
+            } else if let Some(sprites) = batch.as_sprite_vec() {
+                counts.sprites += sprites.len();
+                self.instance_bytes.sprites += sprites.len() * size_of::<InstancedSprite>();
+            } else if let Some(shadows) = batch.as_shadow_vec() {
+                counts.shadows += shadows.len();
+                self.instance_bytes.shadows += shadows.len() * size_of::<InstancedShadow>();
+            } else if let Some(ellipses) = batch.as_ellipse_vec() {
+                counts.ellipses += ellipses.len();
+                self.instance_bytes.ellipses += ellipses.len() * size_of::<InstancedEllipse>();
+            } else if let Some(line_segments) = batch.as_line_segment_vec() {
+                counts.line_segments += line_segments.len();
+                self.instance_bytes.line_segments +=
+                    line_segments.len() * size_of::<InstancedLineSegment>();
+            } else if let Some(undercurls) = batch.as_undercurl_vec() {
+                counts.undercurls += undercurls.len();
+                self.instance_bytes.undercurls +=
+                    undercurls.len() * size_of::<InstancedUndercurl>();
+            }
+        }
+
+        self.primitives.add(&counts);
+        counts
+    }
+}
+
+impl Display for SceneStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Primitives: {}", self.primitives)?;
+        writeln!(
+            f,
+            "Batches: {} ({} shared, {} mutable)",
+            self.batches, self.shared_batches, self.mutable_batches
+        )?;
+        for (index, layer) in self.layers.iter().enumerate() {
+            write!(
+                f,
+                "  Layer {}: {} batches, {}",
+                index, layer.batches, layer.primitives
+            )?;
+            if layer.mask_batches > 0 {
+                write!(
+                    f,
+                    "; mask {} batches, {}",
+                    layer.mask_batches, layer.mask_primitives
+                )?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "Unique glyphs:")?;
+        for glyphs in &self.glyphs {
+            writeln!(
+                f,
+                "  Font {} at {}px: {}",
+                glyphs.font.raw(),
+                glyphs.size,
+                glyphs.unique_glyphs
+            )?;
+        }
+
+        writeln!(f, "Font bytes: {}", self.font_bytes)?;
+        writeln!(f, "Texture bytes: {}", self.texture_bytes)?;
+        let bytes = &self.instance_bytes;
+        writeln!(
+            f,
+            "Instance bytes per frame: {} (blurs {}, quads {}, glyphs {}, paths {}, sprites {}, shadows {}, ellipses {}, line segments {}, undercurls {})",
+            bytes.total(),
+            bytes.blurs,
+            bytes.quads,
+            bytes.glyphs,
+            bytes.paths,
+            bytes.sprites,
+            bytes.shadows,
+            bytes.ellipses,
+            bytes.line_segments,
+            bytes.undercurls
+        )
+    }
+}
+
+impl Display for PrimitiveCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} blurs, {} quads, {} glyph runs ({} glyphs), {} paths, {} sprites, {} shadows, {} ellipses, {} line segments, {} undercurls",
+            self.blurs,
+            self.quads,
+            self.glyph_runs,
+            self.glyphs,
+            self.paths,
+            self.sprites,
+            self.shadows,
+            self.ellipses,
+            self.line_segments,
+            self.undercurls
+        )
+    }
+}
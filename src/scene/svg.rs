@@ -0,0 +1,962 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+    io::Cursor,
+};
+
+use base64::prelude::*;
+use glamour::{Point2, Rect, Size2};
+use image::{ImageFormat, RgbaImage};
+use palette::Srgba;
+use parley::swash::{
+    scale::ScaleContext,
+    zeno::{Angle, Command, PathData, Transform as OutlineTransform, Vector},
+};
+
+use super::{
+    Blur, Border, BorderDash, BorderWidths, CornerRadii, Ellipse, GlyphRun, Gradient,
+    GradientInterpolation, GradientShape, LayerContents, LineCap, LineJoin, LineSegment, Path,
+    PathCommand, PrimitiveBatch, Quad, Resources, Scene, Shadow, Sprite, TextureId, Transform,
+    Undercurl,
+};
+
+// Quads used to clear a layer are sized to cover any possible surface. They are clamped to the
+// content bounds when exported instead of being written out at their full size.
+const CLEAR_SIZE: f32 = f32::MAX / 4.;
+
+impl Scene {
+    /// Exports the scene as an SVG document without touching the GPU. Quads become rounded
+    /// rects with their borders drawn as rings and gradients as svg gradients, paths and glyph
+    /// runs become svg paths with glyph outlines taken from the stored fonts, sprites become
+    /// embedded png images, shadows become gaussian blur filters, ellipses and line segments
+    /// become `ellipse` and `line` elements, undercurls become sampled polylines, and layer clips
+    /// and masks map to `clipPath` and `mask` elements. Background blurs and edge blur have no
+    /// svg equivalent and are drawn as their unblurred shapes. Svg strokes have a single cap, so
+    /// open paths with different start and end caps are stroked with butt caps and get their caps
+    /// from markers. Dashed strokes use the start cap throughout, since markers would only cap
+    /// the ends of the whole path rather than each dash.
+    ///
+    /// The document covers the area from the origin to the furthest extent of the content.
+    pub fn to_svg(&self) -> String {
+        let mut writer = SvgWriter {
+            size: self.content_extent(),
+            defs: String::new(),
+            body: String::new(),
+            scale_context: ScaleContext::new(),
+            texture_ids: HashMap::new(),
+            filter_count: 0,
+            border_count: 0,
+            gradient_count: 0,
+            marker_count: 0,
+        };
+        writer
+            .write_scene(self)
+            .expect("Writing to a String cannot fail");
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = writer.size.x,
+            h = writer.size.y
+        )
+        .unwrap();
+        if !writer.defs.is_empty() {
+            writeln!(svg, "<defs>\n{}</defs>", writer.defs).unwrap();
+        }
+        svg.push_str(&writer.body);
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn content_extent(&self) -> Point2 {
+        let mut extent = Point2::new(1., 1.);
+        let mut include = |point: Point2| {
+            if point.x.is_finite() && point.x < CLEAR_SIZE {
+                extent.x = extent.x.max(point.x);
+            }
+            if point.y.is_finite() && point.y < CLEAR_SIZE {
+                extent.y = extent.y.max(point.y);
+            }
+        };
+
+        for layer in &self.layers {
+            if let Some(clip) = layer.clip {
+                include(Point2::new(
+                    (clip.origin.x + clip.width()) as f32,
+                    (clip.origin.y + clip.height()) as f32,
+                ));
+            }
+
+            for contents in layer.mask.iter().chain([&layer.contents]) {
+                for batch in &contents.primitives {
+                    for quad in batch.as_quad_vec().into_iter().flatten() {
+                        let transform = quad.transform.unwrap_or_default();
+                        for corner in rect_corners(quad.region) {
+                            include(transform.apply(corner));
+                        }
+                    }
+                    for blur in batch.as_blur_vec().into_iter().flatten() {
+                        include(bottom_right(blur.top_left, blur.size));
+                    }
+                    for path in batch.as_path_vec().into_iter().flatten() {
+                        include(path.start);
+                        for command in &path.commands {
+                            include(command.to());
+                        }
+                    }
+                    for sprite in batch.as_sprite_vec().into_iter().flatten() {
+                        let transform = sprite.transform.unwrap_or_default();
+                        for corner in rect_corners(Rect::new(sprite.top_left, sprite.size)) {
+                            include(transform.apply(corner));
+                        }
+                    }
+                    for shadow in batch.as_shadow_vec().into_iter().flatten() {
+                        let bounds = shadow_bounds(shadow);
+                        include(bottom_right(bounds.origin, bounds.size));
+                    }
+                    for ellipse in batch.as_ellipse_vec().into_iter().flatten() {
+                        let bounds = ellipse.bounds();
+                        include(bottom_right(bounds.origin, bounds.size));
+                    }
+                    for segment in batch.as_line_segment_vec().into_iter().flatten() {
+                        let bounds = segment.bounds();
+                        include(bottom_right(bounds.origin, bounds.size));
+                    }
+                    for undercurl in batch.as_undercurl_vec().into_iter().flatten() {
+                        let bounds = undercurl.bounds();
+                        include(bottom_right(bounds.origin, bounds.size));
+                    }
+                    for glyph_run in batch.as_glyph_run_vec().into_iter().flatten() {
+                        for glyph in &glyph_run.glyphs {
+                            let origin = glyph_run.position + glyph.offset;
+                            include(origin + glamour::vec2!(glyph_run.size, 0.));
+                        }
+                    }
+                }
+            }
+        }
+
+        Point2::new(extent.x.ceil(), extent.y.ceil())
+    }
+}
+
+fn bottom_right(top_left: Point2, size: Size2) -> Point2 {
+    Point2::new(top_left.x + size.width, top_left.y + size.height)
+}
+
+struct SvgWriter {
+    size: Point2,
+    defs: String,
+    body: String,
+    scale_context: ScaleContext,
+    texture_ids: HashMap<TextureId, usize>,
+    filter_count: usize,
+    border_count: usize,
+    gradient_count: usize,
+    marker_count: usize,
+}
+
+impl SvgWriter {
+    fn write_scene(&mut self, scene: &Scene) -> fmt::Result {
+        for (index, layer) in scene.layers.iter().enumerate() {
+            let mut attributes = String::new();
+
+            if let Some(clip) = layer.clip {
+                writeln!(
+                    self.defs,
+                    r#"<clipPath id="clip-{}"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath>"#,
+                    index,
+                    clip.origin.x,
+                    clip.origin.y,
+                    clip.width(),
+                    clip.height()
+                )?;
+                write!(attributes, r#" clip-path="url(#clip-{})""#, index)?;
+            }
+
+            if let Some(mask) = &layer.mask {
+                // Like the renderer, only the alpha of the mask layer is used
+                let mut contents = String::new();
+                self.write_contents(&mut contents, &scene.resources, mask)?;
+                writeln!(
+                    self.defs,
+                    r#"<mask id="mask-{}" style="mask-type:alpha" maskUnits="userSpaceOnUse" x="0" y="0" width="{}" height="{}">"#,
+                    index, self.size.x, self.size.y
+                )?;
+                self.defs.push_str(&contents);
+                self.defs.push_str("</mask>\n");
+                write!(attributes, r#" mask="url(#mask-{})""#, index)?;
+            }
+
+            let mut contents = String::new();
+            self.write_contents(&mut contents, &scene.resources, &layer.contents)?;
+            writeln!(self.body, "<g{}>", attributes)?;
+            self.body.push_str(&contents);
+            self.body.push_str("</g>\n");
+        }
+
+        Ok(())
+    }
+
+    fn write_contents(
+        &mut self,
+        out: &mut String,
+        resources: &Resources,
+        contents: &LayerContents,
+    ) -> fmt::Result {
+        for batch in &contents.primitives {
+            self.write_batch(out, resources, batch)?;
+        }
+        Ok(())
+    }
+
+    fn write_batch(
+        &mut self,
+        out: &mut String,
+        resources: &Resources,
+        batch: &PrimitiveBatch,
+    ) -> fmt::Result {
+        for shadow in batch.as_shadow_vec().into_iter().flatten() {
+            self.write_shadow(out, shadow)?;
+        }
+        for quad in batch.as_quad_vec().into_iter().flatten() {
+            // The fill and border are grouped so they share the transform
+            let transform = quad.transform.filter(|transform| !transform.is_identity());
+            if let Some(transform) = transform {
+                writeln!(out, r#"<g transform="{}">"#, matrix(&transform))?;
+            }
+            self.write_quad(out, quad)?;
+            if let Some(border) = &quad.border {
+                self.write_border(out, quad, border)?;
+            }
+            if transform.is_some() {
+                writeln!(out, "</g>")?;
+            }
+        }
+        for blur in batch.as_blur_vec().into_iter().flatten() {
+            self.write_blur(out, blur)?;
+        }
+        for path in batch.as_path_vec().into_iter().flatten() {
+            self.write_path(out, path)?;
+        }
+        for sprite in batch.as_sprite_vec().into_iter().flatten() {
+            self.write_sprite(out, resources, sprite)?;
+        }
+        for glyph_run in batch.as_glyph_run_vec().into_iter().flatten() {
+            self.write_glyph_run(out, resources, glyph_run)?;
+        }
+        for ellipse in batch.as_ellipse_vec().into_iter().flatten() {
+            write_ellipse(out, ellipse)?;
+        }
+        for segment in batch.as_line_segment_vec().into_iter().flatten() {
+            write_line_segment(out, segment)?;
+        }
+        for undercurl in batch.as_undercurl_vec().into_iter().flatten() {
+            write_undercurl(out, undercurl)?;
+        }
+        Ok(())
+    }
+
+    fn write_quad(&mut self, out: &mut String, quad: &Quad) -> fmt::Result {
+        let paint = match &quad.gradient {
+            Some(gradient) => Paint::Gradient(self.write_gradient(quad.region, gradient)?),
+            None => Paint::Fill(quad.color),
+        };
+
+        let mut region = self.clamp_clear(quad.region);
+        let mut corner_radius = quad.corner_radius;
+        if let Some(border) = &quad.border {
+            // Like the renderer, the fill only covers the area inside the border
+            (region, corner_radius) = inside_border(region, corner_radius, border.widths);
+        }
+
+        let Some(uniform_radius) = corner_radius.as_uniform() else {
+            return writeln!(
+                out,
+                r#"<path d="{}"{}/>"#,
+                rounded_rect_data(region, corner_radius),
+                paint
+            );
+        };
+
+        writeln!(
+            out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}"{}/>"#,
+            region.origin.x,
+            region.origin.y,
+            region.size.width,
+            region.size.height,
+            uniform_radius,
+            paint
+        )
+    }
+
+    // Perceptual interpolation has no svg equivalent, so those gradients mix srgb instead
+    fn write_gradient(&mut self, region: Rect, gradient: &Gradient) -> Result<usize, fmt::Error> {
+        let id = self.gradient_count;
+        self.gradient_count += 1;
+
+        let color_interpolation = match gradient.interpolation {
+            GradientInterpolation::Linear => r#" color-interpolation="linearRGB""#,
+            GradientInterpolation::Perceptual => "",
+        };
+        let [x1, y1, x2, y2] = gradient.parameters(region);
+        let element = match gradient.shape {
+            GradientShape::Linear { .. } => {
+                writeln!(
+                    self.defs,
+                    r#"<linearGradient id="gradient-{}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}"{}>"#,
+                    id, x1, y1, x2, y2, color_interpolation
+                )?;
+                "linearGradient"
+            }
+            GradientShape::Radial { .. } => {
+                writeln!(
+                    self.defs,
+                    r#"<radialGradient id="gradient-{}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}"{}>"#,
+                    id, x1, y1, x2, color_interpolation
+                )?;
+                "radialGradient"
+            }
+        };
+
+        for stop in &gradient.stops {
+            write!(
+                self.defs,
+                r#"<stop offset="{}" stop-color="{}""#,
+                stop.offset,
+                hex(&stop.color)
+            )?;
+            if stop.color.alpha < 1. {
+                write!(self.defs, r#" stop-opacity="{}""#, stop.color.alpha)?;
+            }
+            writeln!(self.defs, "/>")?;
+        }
+        writeln!(self.defs, "</{}>", element)?;
+        Ok(id)
+    }
+
+    // Borders are written as the outer rounded rect with the inner one cut out. When the sides
+    // have different colors, the ring is drawn once per side clipped to the trapezoid between
+    // that side's outer and inner corners.
+    fn write_border(&mut self, out: &mut String, quad: &Quad, border: &Border) -> fmt::Result {
+        let outer = self.clamp_clear(quad.region);
+        let (inner, inner_radii) = inside_border(outer, quad.corner_radius, border.widths);
+        let data = format!(
+            "{} {}",
+            rounded_rect_data(outer, quad.corner_radius),
+            rounded_rect_data(inner, inner_radii)
+        );
+
+        let uniform_width = border
+            .widths
+            .to_array()
+            .iter()
+            .all(|width| *width == border.widths.top);
+        if let (Some(dash), Some(color), true) =
+            (border.dash, border.colors.as_uniform(), uniform_width)
+        {
+            return write_dashed_border(
+                out,
+                outer,
+                quad.corner_radius,
+                border.widths.top,
+                dash,
+                color,
+            );
+        }
+
+        if let Some(color) = border.colors.as_uniform() {
+            return writeln!(
+                out,
+                r#"<path d="{}" fill-rule="evenodd"{}/>"#,
+                data,
+                Paint::Fill(color)
+            );
+        }
+
+        let outer_corners = rect_corners(outer);
+        let inner_corners = rect_corners(inner);
+        for (side, color) in border.colors.to_array().into_iter().enumerate() {
+            let (start, end) = (side, (side + 1) % 4);
+            let clip_id = self.border_count;
+            self.border_count += 1;
+            writeln!(
+                self.defs,
+                r#"<clipPath id="border-{}"><polygon points="{} {} {} {} {} {} {} {}"/></clipPath>"#,
+                clip_id,
+                outer_corners[start].x,
+                outer_corners[start].y,
+                outer_corners[end].x,
+                outer_corners[end].y,
+                inner_corners[end].x,
+                inner_corners[end].y,
+                inner_corners[start].x,
+                inner_corners[start].y,
+            )?;
+            writeln!(
+                out,
+                r#"<path d="{}" fill-rule="evenodd" clip-path="url(#border-{})"{}/>"#,
+                data,
+                clip_id,
+                Paint::Fill(color)
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_path(&mut self, out: &mut String, path: &Path) -> fmt::Result {
+        let mut data = format!("M{} {}", path.start.x, path.start.y);
+        for command in &path.commands {
+            match command {
+                PathCommand::CubicBezierTo {
+                    control1,
+                    control2,
+                    to,
+                } => write!(
+                    data,
+                    " C{} {} {} {} {} {}",
+                    control1.x, control1.y, control2.x, control2.y, to.x, to.y
+                )?,
+                PathCommand::QuadraticBezierTo { control, to } => {
+                    write!(data, " Q{} {} {} {}", control.x, control.y, to.x, to.y)?
+                }
+                PathCommand::LineTo { to } => write!(data, " L{} {}", to.x, to.y)?,
+            }
+        }
+        if !path.open {
+            data.push_str(" Z");
+        }
+
+        let fill = match path.fill {
+            Some(fill) => Paint::Fill(fill),
+            None => Paint::None("fill"),
+        };
+        let stroke = match path.stroke {
+            Some((width, color)) => Paint::Stroke(width, color),
+            None => Paint::None("stroke"),
+        };
+        let mut stroke_style = String::new();
+        if let (Some((_, color)), Some(style)) = (path.stroke, &path.stroke_style) {
+            let cap_markers =
+                path.open && style.start_cap != style.end_cap && style.dash_array.is_empty();
+            let cap = if cap_markers {
+                LineCap::Butt
+            } else {
+                style.start_cap
+            };
+            write!(
+                stroke_style,
+                r#" stroke-linecap="{}" stroke-linejoin="{}" stroke-miterlimit="{}""#,
+                line_cap(cap),
+                line_join(style.join),
+                style.miter_limit.max(1.)
+            )?;
+            if cap_markers {
+                let id = self.marker_count;
+                self.marker_count += 1;
+                let ends = [
+                    (style.start_cap, "start", false),
+                    (style.end_cap, "end", true),
+                ];
+                for (cap, end, at_end) in ends {
+                    let Some(data) = cap_marker_data(cap, at_end) else {
+                        continue;
+                    };
+                    writeln!(
+                        self.defs,
+                        r#"<marker id="cap-{}-{}" markerUnits="strokeWidth" orient="auto" overflow="visible"><path d="{}"{}/></marker>"#,
+                        id,
+                        end,
+                        data,
+                        Paint::Fill(color)
+                    )?;
+                    write!(
+                        stroke_style,
+                        r#" marker-{}="url(#cap-{}-{})""#,
+                        end, id, end
+                    )?;
+                }
+            }
+            if !style.dash_array.is_empty() {
+                let dash_array: Vec<_> = style.dash_array.iter().map(f32::to_string).collect();
+                write!(
+                    stroke_style,
+                    r#" stroke-dasharray="{}" stroke-dashoffset="{}""#,
+                    dash_array.join(" "),
+                    style.dash_offset
+                )?;
+            }
+        }
+        writeln!(
+            out,
+            r#"<path d="{}"{}{}{}/>"#,
+            data, fill, stroke, stroke_style
+        )
+    }
+
+    // Shadows blur their shape with a gaussian filter. Outset shadows are clipped to outside of
+    // the box, and inset shadows blur everything around the shape and are clipped to the box.
+    fn write_shadow(&mut self, out: &mut String, shadow: &Shadow) -> fmt::Result {
+        let id = self.filter_count;
+        self.filter_count += 1;
+
+        let bounds = shadow_bounds(shadow);
+        let bounds_data = rounded_rect_data(bounds, CornerRadii::default());
+        let box_data = rounded_rect_data(shadow.region, shadow.corner_radius);
+        let (shape, shape_radii) = shadow.shape();
+        let shape_data = rounded_rect_data(shape, shape_radii);
+
+        writeln!(
+            self.defs,
+            r#"<filter id="shadow-{}" filterUnits="userSpaceOnUse" x="{}" y="{}" width="{}" height="{}"><feGaussianBlur stdDeviation="{}"/></filter>"#,
+            id,
+            bounds.origin.x,
+            bounds.origin.y,
+            bounds.size.width,
+            bounds.size.height,
+            shadow.blur.max(0.) / 2.
+        )?;
+
+        let (clip, blurred) = if shadow.inset {
+            (box_data, format!("{} {}", bounds_data, shape_data))
+        } else {
+            (format!("{} {}", bounds_data, box_data), shape_data)
+        };
+        writeln!(
+            self.defs,
+            r#"<clipPath id="shadow-clip-{}"><path d="{}" clip-rule="evenodd"/></clipPath>"#,
+            id, clip
+        )?;
+        writeln!(
+            out,
+            r#"<path d="{}" fill-rule="evenodd" filter="url(#shadow-{})" clip-path="url(#shadow-clip-{})"{}/>"#,
+            blurred,
+            id,
+            id,
+            Paint::Fill(shadow.color)
+        )
+    }
+
+    fn write_blur(&self, out: &mut String, blur: &Blur) -> fmt::Result {
+        let region = self.clamp_clear(Rect::new(blur.top_left, blur.size));
+        writeln!(
+            out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}"{}/>"#,
+            region.origin.x,
+            region.origin.y,
+            region.size.width,
+            region.size.height,
+            blur.corner_radius,
+            Paint::Fill(blur.color)
+        )
+    }
+
+    fn clamp_clear(&self, region: Rect) -> Rect {
+        if region.size.width < CLEAR_SIZE && region.size.height < CLEAR_SIZE {
+            return region;
+        }
+
+        let min = Point2::new(region.origin.x.max(0.), region.origin.y.max(0.));
+        let max = bottom_right(region.origin, region.size);
+        let max = Point2::new(
+            max.x.min(self.size.x).max(min.x),
+            max.y.min(self.size.y).max(min.y),
+        );
+        Rect::new(min, Size2::new(max.x - min.x, max.y - min.y))
+    }
+
+    fn write_sprite(
+        &mut self,
+        out: &mut String,
+        resources: &Resources,
+        sprite: &Sprite<TextureId>,
+    ) -> fmt::Result {
+        let Some(id) = self.texture_id(resources, sprite.texture)? else {
+            return Ok(());
+        };
+
+        let mut filter = String::new();
+        let color = sprite.color;
+        if (color.red, color.green, color.blue, color.alpha) != (1., 1., 1., 1.) {
+            // Sprites are tinted by multiplying with their color
+            let filter_id = self.filter_count;
+            self.filter_count += 1;
+            writeln!(
+                self.defs,
+                r#"<filter id="tint-{}" color-interpolation-filters="sRGB"><feColorMatrix type="matrix" values="{} 0 0 0 0 0 {} 0 0 0 0 0 {} 0 0 0 0 0 {} 0"/></filter>"#,
+                filter_id, color.red, color.green, color.blue, color.alpha
+            )?;
+            write!(filter, r#" filter="url(#tint-{})""#, filter_id)?;
+        }
+
+        let mut transform = String::new();
+        if let Some(sprite_transform) = sprite.transform.filter(|t| !t.is_identity()) {
+            write!(transform, "{} ", matrix(&sprite_transform))?;
+        }
+
+        writeln!(
+            out,
+            r##"<use href="#texture-{}" transform="{}translate({} {}) scale({} {})"{}/>"##,
+            id,
+            transform,
+            sprite.top_left.x,
+            sprite.top_left.y,
+            sprite.size.width,
+            sprite.size.height,
+            filter
+        )
+    }
+
+    // Textures are embedded once as unit sized images and scaled into place by each sprite
+    fn texture_id(
+        &mut self,
+        resources: &Resources,
+        texture: TextureId,
+    ) -> Result<Option<usize>, fmt::Error> {
+        if let Some(id) = self.texture_ids.get(&texture) {
+            return Ok(Some(*id));
+        }
+
+        let Some(texture_data) = resources.textures.get(&texture) else {
+            return Ok(None);
+        };
+        let Some(image) = RgbaImage::from_raw(
+            texture_data.size.width,
+            texture_data.size.height,
+            texture_data.data.clone(),
+        ) else {
+            return Ok(None);
+        };
+        let mut png = Vec::new();
+        if image
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .is_err()
+        {
+            return Ok(None);
+        }
+
+        let id = self.texture_ids.len();
+        writeln!(
+            self.defs,
+            r#"<image id="texture-{}" width="1" height="1" preserveAspectRatio="none" href="data:image/png;base64,{}"/>"#,
+            id,
+            BASE64_STANDARD.encode(png)
+        )?;
+        self.texture_ids.insert(texture, id);
+        Ok(Some(id))
+    }
+
+    fn write_glyph_run(
+        &mut self,
+        out: &mut String,
+        resources: &Resources,
+        glyph_run: &GlyphRun,
+    ) -> fmt::Result {
+        let Some(font_ref) = resources
+            .fonts
+            .get(&glyph_run.font_id)
+            .and_then(|font| font.as_swash_font_ref(glyph_run.font_index))
+        else {
+            return Ok(());
+        };
+
+        let mut scaler = self
+            .scale_context
+            .builder(font_ref)
+            .size(glyph_run.size)
+            .variations(&glyph_run.synthesis.vars)
+            .normalized_coords(&glyph_run.normalized_coords)
+            .build();
+
+        let mut data = String::new();
+        for glyph in &glyph_run.glyphs {
+            let Some(mut outline) = scaler.scale_outline(glyph.id) else {
+                continue;
+            };
+            if glyph_run.synthesis.skew != 0.0 {
+                outline.transform(&OutlineTransform::skew(
+                    Angle::from_degrees(*glyph_run.synthesis.skew),
+                    Angle::from_degrees(0.0),
+                ));
+            }
+            if glyph_run.synthesis.embolden {
+                outline.embolden(1.0, 1.0);
+            }
+
+            // Outlines are y up relative to the glyph origin on the baseline
+            let origin = glyph_run.position + glyph.offset;
+            let point = |point: Vector| format!("{} {}", origin.x + point.x, origin.y - point.y);
+            for command in outline.path().commands() {
+                match command {
+                    Command::MoveTo(to) => write!(data, "M{}", point(to))?,
+                    Command::LineTo(to) => write!(data, "L{}", point(to))?,
+                    Command::QuadTo(control, to) => {
+                        write!(data, "Q{} {}", point(control), point(to))?
+                    }
+                    Command::CurveTo(control1, control2, to) => write!(
+                        data,
+                        "C{} {} {}",
+                        point(control1),
+                        point(control2),
+                        point(to)
+                    )?,
+                    Command::Close => data.push('Z'),
+                }
+            }
+        }
+
+        if data.is_empty() {
+            return Ok(());
+        }
+        writeln!(
+            out,
+            r#"<path d="{}"{}/>"#,
+            data,
+            Paint::Fill(glyph_run.color)
+        )
+    }
+}
+
+// Dashed borders are stroked along the middle of the border with the pattern stretched to fit
+// like in the quad shader. Borders with different widths or colors per side are drawn solid.
+fn write_dashed_border(
+    out: &mut String,
+    region: Rect,
+    radii: CornerRadii,
+    width: f32,
+    dash: BorderDash,
+    color: Srgba,
+) -> fmt::Result {
+    let (middle, middle_radii) = inside_border(region, radii, BorderWidths::from(width / 2.));
+    let radii_sum: f32 = middle_radii.to_array().iter().sum();
+    let perimeter = 2. * (middle.size.width + middle.size.height) - 2. * radii_sum
+        + radii_sum * std::f32::consts::FRAC_PI_2;
+    let period = dash.on + dash.off;
+    let scale = if period > 0. {
+        perimeter / ((perimeter / period).round().max(1.) * period)
+    } else {
+        1.
+    };
+
+    writeln!(
+        out,
+        r#"<path d="{}" fill="none"{} stroke-dasharray="{} {}" stroke-dashoffset="{}" stroke-linecap="{}"/>"#,
+        rounded_rect_data(middle, middle_radii),
+        Paint::Stroke(width, color),
+        dash.on * scale,
+        dash.off * scale,
+        -dash.phase * scale,
+        line_cap(dash.cap)
+    )
+}
+
+// The area a shadow's blur can reach, which also contains the box casting it
+fn shadow_bounds(shadow: &Shadow) -> Rect {
+    let (shape, _) = shadow.shape();
+    let extent = shadow.blur.max(0.) * 1.5;
+    let min = Point2::new(
+        shape.origin.x.min(shadow.region.origin.x) - extent,
+        shape.origin.y.min(shadow.region.origin.y) - extent,
+    );
+    let shape_max = bottom_right(shape.origin, shape.size);
+    let box_max = bottom_right(shadow.region.origin, shadow.region.size);
+    let max = Point2::new(
+        shape_max.x.max(box_max.x) + extent,
+        shape_max.y.max(box_max.y) + extent,
+    );
+    Rect::new(min, Size2::new(max.x - min.x, max.y - min.y))
+}
+
+// The region and corner radii left for the fill inside a border, matching the quad shader
+fn inside_border(region: Rect, radii: CornerRadii, widths: BorderWidths) -> (Rect, CornerRadii) {
+    let origin = Point2::new(region.origin.x + widths.left, region.origin.y + widths.top);
+    let size = Size2::new(
+        (region.size.width - widths.left - widths.right).max(0.),
+        (region.size.height - widths.top - widths.bottom).max(0.),
+    );
+    let radii = CornerRadii::new(
+        (radii.top_left - widths.left.max(widths.top)).max(0.),
+        (radii.top_right - widths.top.max(widths.right)).max(0.),
+        (radii.bottom_right - widths.right.max(widths.bottom)).max(0.),
+        (radii.bottom_left - widths.bottom.max(widths.left)).max(0.),
+    );
+    (Rect::new(origin, size), radii)
+}
+
+// Top left, top right, bottom right, bottom left, so that side n runs from corner n to n + 1
+fn rect_corners(region: Rect) -> [Point2; 4] {
+    let Rect { origin, size } = region;
+    let (right, bottom) = (origin.x + size.width, origin.y + size.height);
+    [
+        origin,
+        Point2::new(right, origin.y),
+        Point2::new(right, bottom),
+        Point2::new(origin.x, bottom),
+    ]
+}
+
+// Traces the outline of a rectangle clockwise from the top left, with an arc for each rounded
+// corner
+fn rounded_rect_data(region: Rect, radii: CornerRadii) -> String {
+    let Rect { origin, size } = region;
+    let (right, bottom) = (origin.x + size.width, origin.y + size.height);
+    let CornerRadii {
+        top_left,
+        top_right,
+        bottom_right,
+        bottom_left,
+    } = radii;
+    format!(
+        "M{} {} H{} A{r1} {r1} 0 0 1 {right} {} V{} A{r2} {r2} 0 0 1 {} {bottom} H{} A{r3} {r3} 0 0 1 {} {} V{} A{r0} {r0} 0 0 1 {} {} Z",
+        origin.x + top_left,
+        origin.y,
+        right - top_right,
+        origin.y + top_right,
+        bottom - bottom_right,
+        right - bottom_right,
+        origin.x + bottom_left,
+        origin.x,
+        bottom - bottom_left,
+        origin.y + top_left,
+        origin.x + top_left,
+        origin.y,
+        r0 = top_left,
+        r1 = top_right,
+        r2 = bottom_right,
+        r3 = bottom_left,
+    )
+}
+
+fn write_ellipse(out: &mut String, ellipse: &Ellipse) -> fmt::Result {
+    let stroke = if ellipse.stroke_width > 0. {
+        Paint::Stroke(ellipse.stroke_width, ellipse.stroke_color)
+    } else {
+        Paint::None("stroke")
+    };
+    writeln!(
+        out,
+        r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}"{}{}/>"#,
+        ellipse.center.x,
+        ellipse.center.y,
+        ellipse.radii.x.abs(),
+        ellipse.radii.y.abs(),
+        Paint::Fill(ellipse.color),
+        stroke
+    )
+}
+
+fn write_line_segment(out: &mut String, segment: &LineSegment) -> fmt::Result {
+    writeln!(
+        out,
+        r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{} stroke-linecap="{}"/>"#,
+        segment.start.x,
+        segment.start.y,
+        segment.end.x,
+        segment.end.y,
+        Paint::Stroke(segment.width, segment.color),
+        line_cap(segment.cap)
+    )
+}
+
+// Undercurls are sampled into a polyline with a few points per wavelength
+fn write_undercurl(out: &mut String, undercurl: &Undercurl) -> fmt::Result {
+    let end = undercurl.start.x + undercurl.length.max(0.);
+    let step = if undercurl.wavelength > 0. {
+        undercurl.wavelength / 8.
+    } else {
+        undercurl.length.max(1.)
+    };
+
+    let y = |x: f32| undercurl.start.y + undercurl.offset_at(x);
+    let mut x = undercurl.start.x;
+    let mut data = format!("M{} {}", x, y(x));
+    while x < end {
+        x = (x + step).min(end);
+        write!(data, " L{} {}", x, y(x))?;
+    }
+
+    writeln!(
+        out,
+        r#"<path d="{}" fill="none"{}/>"#,
+        data,
+        Paint::Stroke(undercurl.thickness, undercurl.color)
+    )
+}
+
+// Formats colors as svg paint attributes
+enum Paint {
+    Fill(Srgba),
+    Stroke(f32, Srgba),
+    None(&'static str),
+    // A fill referring to a gradient in the defs by its id
+    Gradient(usize),
+}
+
+fn matrix(transform: &Transform) -> String {
+    let [a, b, c, d, e, f] = transform.to_array();
+    format!("matrix({} {} {} {} {} {})", a, b, c, d, e, f)
+}
+
+fn line_cap(cap: LineCap) -> &'static str {
+    match cap {
+        LineCap::Butt => "butt",
+        LineCap::Round => "round",
+        LineCap::Square => "square",
+    }
+}
+
+// The shape of a cap drawn by a marker in units of the stroke width, with x pointing along the
+// path at its end and away from the path at its start. Butt caps don't need a marker.
+fn cap_marker_data(cap: LineCap, at_end: bool) -> Option<&'static str> {
+    match (cap, at_end) {
+        (LineCap::Butt, _) => None,
+        (LineCap::Round, false) => Some("M0 -0.5 A0.5 0.5 0 0 0 0 0.5 Z"),
+        (LineCap::Round, true) => Some("M0 -0.5 A0.5 0.5 0 0 1 0 0.5 Z"),
+        (LineCap::Square, false) => Some("M0 -0.5 H-0.5 V0.5 H0 Z"),
+        (LineCap::Square, true) => Some("M0 -0.5 H0.5 V0.5 H0 Z"),
+    }
+}
+
+fn line_join(join: LineJoin) -> &'static str {
+    match join {
+        LineJoin::Miter => "miter",
+        LineJoin::MiterClip => "miter-clip",
+        LineJoin::Round => "round",
+        LineJoin::Bevel => "bevel",
+    }
+}
+
+fn hex(color: &Srgba) -> String {
+    let channel = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(color.red),
+        channel(color.green),
+        channel(color.blue)
+    )
+}
+
+impl fmt::Display for Paint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Paint::Fill(color) => {
+                write!(f, r#" fill="{}""#, hex(color))?;
+                if color.alpha < 1. {
+                    write!(f, r#" fill-opacity="{}""#, color.alpha)?;
+                }
+                Ok(())
+            }
+            Paint::Stroke(width, color) => {
+                write!(f, r#" stroke="{}" stroke-width="{}""#, hex(color), width)?;
+                if color.alpha < 1. {
+                    write!(f, r#" stroke-opacity="{}""#, color.alpha)?;
+                }
+                Ok(())
+            }
+            Paint::None(attribute) => write!(f, r#" {}="none""#, attribute),
+            Paint::Gradient(id) => write!(f, r#" fill="url(#gradient-{})""#, id),
+        }
+    }
+}
@@ -0,0 +1,888 @@
+use std::{f32::consts::PI, fmt};
+
+use glamour::{point2, vec2, Point2, Vector2};
+use palette::Srgba;
+
+use super::{Path, PathCommand};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgImportError {
+    /// Byte offset into the path data or document where the problem was found.
+    pub offset: usize,
+    pub message: String,
+}
+
+impl SvgImportError {
+    fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SvgImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for SvgImportError {}
+
+impl Path {
+    /// Parses the contents of an svg `d` attribute into paths. Each subpath becomes its own path
+    /// without fill or stroke, and is open unless it was closed with `Z`. Relative commands and
+    /// the `H`, `V`, `S` and `T` shorthands are resolved to absolute points, and arcs are
+    /// approximated with cubic beziers.
+    pub fn from_svg_path_data(data: &str) -> Result<Vec<Path>, SvgImportError> {
+        PathDataParser::new(data).parse()
+    }
+
+    /// Loads the `path`, `rect`, `circle`, `ellipse` and `polygon` elements of an svg document
+    /// along with their `fill`, `fill-rule`, `stroke`, `stroke-width` and opacity attributes.
+    /// Only attributes set on the elements themselves are used; groups, transforms, gradients and
+    /// css classes are ignored, and colors which can't be parsed leave the default paint.
+    ///
+    /// Elements with several subpaths are stroked one path per subpath. Their fill becomes a
+    /// single path with the subpaths joined by bridges, which cuts holes where they overlap, or
+    /// one path per subpath for `nonzero` fills whose subpaths all wind the same way. `nonzero`
+    /// fills mixing directions are joined as well, which matches where reversed subpaths cut
+    /// holes but also cuts overlaps between subpaths winding the same way.
+    pub fn from_svg(svg: &str) -> Result<Vec<Path>, SvgImportError> {
+        let mut paths = Vec::new();
+        for element in XmlElements::new(svg) {
+            let element = element?;
+            let data = match element.name {
+                "path" => element.attribute("d").unwrap_or_default().to_string(),
+                "rect" => rect_path_data(&element)?,
+                "circle" => {
+                    let radius = element.number("r")?;
+                    ellipse_path_data(&element, radius, radius)?
+                }
+                "ellipse" => {
+                    ellipse_path_data(&element, element.number("rx")?, element.number("ry")?)?
+                }
+                "polygon" => polygon_path_data(&element)?,
+                _ => continue,
+            };
+
+            let style = Style::from_element(&element)?;
+            let subpaths = Path::from_svg_path_data(&data).map_err(|error| {
+                SvgImportError::new(element.offset, format!("Invalid path data: {}", error))
+            })?;
+            if subpaths.len() > 1 {
+                if let Some(fill) = style.fill {
+                    paths.extend(fill_subpaths(&subpaths, style.even_odd, fill));
+                }
+                if let Some(stroke) = style.stroke {
+                    for mut path in subpaths {
+                        path.set_stroke(style.stroke_width, stroke);
+                        paths.push(path);
+                    }
+                }
+                continue;
+            }
+
+            for mut path in subpaths {
+                if let Some(fill) = style.fill {
+                    // svg fills open subpaths as if they were closed
+                    path.open = false;
+                    path.set_fill(fill);
+                }
+                if let Some(stroke) = style.stroke {
+                    path.set_stroke(style.stroke_width, stroke);
+                }
+                if path.fill.is_some() || path.stroke.is_some() {
+                    paths.push(path);
+                }
+            }
+        }
+
+        Ok(paths)
+    }
+}
+
+struct PathDataParser<'a> {
+    data: &'a str,
+    position: usize,
+    paths: Vec<Path>,
+    current: Option<Path>,
+    point: Point2,
+    subpath_start: Point2,
+    // The second control point of the previous cubic or the control point of the previous
+    // quadratic, used to reflect the control point of the S and T shorthands
+    last_cubic_control: Option<Point2>,
+    last_quadratic_control: Option<Point2>,
+}
+
+impl<'a> PathDataParser<'a> {
+    fn new(data: &'a str) -> Self {
+        Self {
+            data,
+            position: 0,
+            paths: Vec::new(),
+            current: None,
+            point: point2!(0., 0.),
+            subpath_start: point2!(0., 0.),
+            last_cubic_control: None,
+            last_quadratic_control: None,
+        }
+    }
+
+    fn parse(mut self) -> Result<Vec<Path>, SvgImportError> {
+        let mut command = None;
+        loop {
+            self.skip_separators();
+            let Some(next) = self.peek() else {
+                break;
+            };
+
+            if next.is_ascii_alphabetic() {
+                self.position += 1;
+                command = Some(next);
+            } else if command.is_none() {
+                return Err(self.error("Expected a command"));
+            }
+
+            let current_command = command.unwrap();
+            self.parse_command(current_command)?;
+
+            // Coordinates after a move are implicit line commands
+            command = match current_command {
+                b'M' => Some(b'L'),
+                b'm' => Some(b'l'),
+                b'Z' | b'z' => None,
+                other => Some(other),
+            };
+        }
+
+        self.finish_subpath(false);
+        Ok(self.paths)
+    }
+
+    fn parse_command(&mut self, command: u8) -> Result<(), SvgImportError> {
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative {
+            vec2!(self.point.x, self.point.y)
+        } else {
+            vec2!(0., 0.)
+        };
+
+        let mut cubic_control = None;
+        let mut quadratic_control = None;
+        match command.to_ascii_uppercase() {
+            b'M' => {
+                let to = self.point()? + origin;
+                self.finish_subpath(false);
+                self.point = to;
+                self.subpath_start = to;
+            }
+            b'L' => {
+                let to = self.point()? + origin;
+                self.push(PathCommand::LineTo { to });
+            }
+            b'H' => {
+                let x = self.number()? + origin.x;
+                self.push(PathCommand::LineTo {
+                    to: point2!(x, self.point.y),
+                });
+            }
+            b'V' => {
+                let y = self.number()? + origin.y;
+                self.push(PathCommand::LineTo {
+                    to: point2!(self.point.x, y),
+                });
+            }
+            b'C' => {
+                let control1 = self.point()? + origin;
+                let control2 = self.point()? + origin;
+                let to = self.point()? + origin;
+                cubic_control = Some(control2);
+                self.push(PathCommand::CubicBezierTo {
+                    control1,
+                    control2,
+                    to,
+                });
+            }
+            b'S' => {
+                let control1 = reflect(self.last_cubic_control, self.point);
+                let control2 = self.point()? + origin;
+                let to = self.point()? + origin;
+                cubic_control = Some(control2);
+                self.push(PathCommand::CubicBezierTo {
+                    control1,
+                    control2,
+                    to,
+                });
+            }
+            b'Q' => {
+                let control = self.point()? + origin;
+                let to = self.point()? + origin;
+                quadratic_control = Some(control);
+                self.push(PathCommand::QuadraticBezierTo { control, to });
+            }
+            b'T' => {
+                let control = reflect(self.last_quadratic_control, self.point);
+                let to = self.point()? + origin;
+                quadratic_control = Some(control);
+                self.push(PathCommand::QuadraticBezierTo { control, to });
+            }
+            b'A' => {
+                let radii = vec2!(self.number()?, self.number()?);
+                let rotation = self.number()?;
+                let large_arc = self.flag()?;
+                let sweep = self.flag()?;
+                let to = self.point()? + origin;
+                for command in arc_to_cubics(self.point, radii, rotation, large_arc, sweep, to) {
+                    self.push(command);
+                }
+                self.point = to;
+            }
+            b'Z' => {
+                self.finish_subpath(true);
+                self.point = self.subpath_start;
+            }
+            _ => return Err(self.error(format!("Unknown command '{}'", command as char))),
+        }
+
+        self.last_cubic_control = cubic_control;
+        self.last_quadratic_control = quadratic_control;
+        Ok(())
+    }
+
+    fn push(&mut self, command: PathCommand) {
+        let start = self.point;
+        self.point = command.to();
+        self.current
+            .get_or_insert_with(|| Path::new(start))
+            .commands
+            .push(command);
+    }
+
+    fn finish_subpath(&mut self, closed: bool) {
+        if let Some(mut path) = self.current.take() {
+            path.open = !closed;
+            self.paths.push(path);
+        }
+    }
+
+    fn point(&mut self) -> Result<Point2, SvgImportError> {
+        Ok(point2!(self.number()?, self.number()?))
+    }
+
+    fn number(&mut self) -> Result<f32, SvgImportError> {
+        self.skip_separators();
+        let bytes = self.data.as_bytes();
+        let start = self.position;
+        let mut end = start;
+
+        if matches!(bytes.get(end), Some(b'+' | b'-')) {
+            end += 1;
+        }
+        let mut seen_dot = false;
+        while let Some(&byte) = bytes.get(end) {
+            if byte.is_ascii_digit() {
+                end += 1;
+            } else if byte == b'.' && !seen_dot {
+                seen_dot = true;
+                end += 1;
+            } else {
+                break;
+            }
+        }
+        if matches!(bytes.get(end), Some(b'e' | b'E')) {
+            let mut exponent_end = end + 1;
+            if matches!(bytes.get(exponent_end), Some(b'+' | b'-')) {
+                exponent_end += 1;
+            }
+            if bytes
+                .get(exponent_end)
+                .is_some_and(|byte| byte.is_ascii_digit())
+            {
+                end = exponent_end;
+                while bytes.get(end).is_some_and(|byte| byte.is_ascii_digit()) {
+                    end += 1;
+                }
+            }
+        }
+
+        let number = self.data[start..end]
+            .parse()
+            .map_err(|_| self.error("Expected a number"))?;
+        self.position = end;
+        Ok(number)
+    }
+
+    // Arc flags are a single digit and may be written without a separator after them
+    fn flag(&mut self) -> Result<bool, SvgImportError> {
+        self.skip_separators();
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(self.error("Expected an arc flag")),
+        };
+        self.position += 1;
+        Ok(flag)
+    }
+
+    fn skip_separators(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|byte| byte.is_ascii_whitespace() || byte == b',')
+        {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.as_bytes().get(self.position).copied()
+    }
+
+    fn error(&self, message: impl Into<String>) -> SvgImportError {
+        SvgImportError::new(self.position, message)
+    }
+}
+
+/// The closed fill paths for an element with several subpaths. Paths are filled with the even-odd
+/// rule, so subpaths which all wind the same way under `nonzero` are kept apart to fill their
+/// union, and otherwise they are joined into one path.
+fn fill_subpaths(subpaths: &[Path], even_odd: bool, fill: Srgba) -> Vec<Path> {
+    let mut directions = subpaths
+        .iter()
+        .map(|path| signed_area(path).signum())
+        .filter(|direction| *direction != 0.);
+    let same_direction = match directions.next() {
+        Some(first) => directions.all(|direction| direction == first),
+        None => true,
+    };
+
+    if !even_odd && same_direction {
+        return subpaths
+            .iter()
+            .map(|path| {
+                let mut path = path.clone();
+                path.open = false;
+                path.set_fill(fill);
+                path
+            })
+            .collect();
+    }
+
+    // Each subpath is reached from the end of the first by a bridge which is walked there and
+    // back, so the bridges cancel out of the fill and no MoveTo is needed
+    let (first, rest) = subpaths.split_first().unwrap();
+    let hub = first.commands.last().map_or(first.start, PathCommand::to);
+    let mut joined = first.clone();
+    joined.open = false;
+    for path in rest {
+        joined.add_line_to(path.start);
+        joined.commands.extend_from_slice(&path.commands);
+        joined.add_line_to(path.start);
+        joined.add_line_to(hub);
+    }
+    joined.set_fill(fill);
+    vec![joined]
+}
+
+/// The area enclosed by the points and control points of the path, positive when it winds
+/// clockwise on screen. The control points keep the sign right for curves whose end points
+/// alone enclose nothing.
+fn signed_area(path: &Path) -> f32 {
+    let mut points = vec![path.start];
+    for command in &path.commands {
+        match *command {
+            PathCommand::CubicBezierTo {
+                control1,
+                control2,
+                to,
+            } => points.extend([control1, control2, to]),
+            PathCommand::QuadraticBezierTo { control, to } => points.extend([control, to]),
+            PathCommand::LineTo { to } => points.push(to),
+        }
+    }
+    let next = points.iter().cycle().skip(1);
+    points
+        .iter()
+        .zip(next)
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<f32>()
+        / 2.
+}
+
+/// Reflects the previous control point about the current point, or uses the current point if
+/// the previous command wasn't a curve of the same kind.
+fn reflect(control: Option<Point2>, point: Point2) -> Point2 {
+    match control {
+        Some(control) => point + (point - control),
+        None => point,
+    }
+}
+
+/// Converts an svg endpoint arc to cubic beziers of at most a quarter turn each, following the
+/// conversion to center parameterization in the svg implementation notes.
+fn arc_to_cubics(
+    from: Point2,
+    radii: Vector2,
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: Point2,
+) -> Vec<PathCommand> {
+    if from == to {
+        return Vec::new();
+    }
+    let (mut rx, mut ry) = (radii.x.abs(), radii.y.abs());
+    if rx == 0. || ry == 0. {
+        return vec![PathCommand::LineTo { to }];
+    }
+
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let half_delta = (from - to) / 2.;
+    let x1 = cos * half_delta.x + sin * half_delta.y;
+    let y1 = -sin * half_delta.x + cos * half_delta.y;
+
+    // Scale up radii which are too small to reach the end point
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1. {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coefficient = (numerator / denominator).max(0.).sqrt();
+    if large_arc == sweep {
+        coefficient = -coefficient;
+    }
+    let center_x1 = coefficient * rx * y1 / ry;
+    let center_y1 = -coefficient * ry * x1 / rx;
+    let middle = from + (to - from) / 2.;
+    let center = point2!(
+        cos * center_x1 - sin * center_y1 + middle.x,
+        sin * center_x1 + cos * center_y1 + middle.y
+    );
+
+    let angle = |x: f32, y: f32| y.atan2(x);
+    let start_angle = angle((x1 - center_x1) / rx, (y1 - center_y1) / ry);
+    let end_angle = angle((-x1 - center_x1) / rx, (-y1 - center_y1) / ry);
+    let mut sweep_angle = end_angle - start_angle;
+    if sweep && sweep_angle < 0. {
+        sweep_angle += 2. * PI;
+    } else if !sweep && sweep_angle > 0. {
+        sweep_angle -= 2. * PI;
+    }
+
+    let point_at = |angle: f32| {
+        let (angle_sin, angle_cos) = angle.sin_cos();
+        point2!(
+            center.x + rx * angle_cos * cos - ry * angle_sin * sin,
+            center.y + rx * angle_cos * sin + ry * angle_sin * cos
+        )
+    };
+    let derivative_at = |angle: f32| {
+        let (angle_sin, angle_cos) = angle.sin_cos();
+        vec2!(
+            -rx * angle_sin * cos - ry * angle_cos * sin,
+            -rx * angle_sin * sin + ry * angle_cos * cos
+        )
+    };
+
+    let segments = (sweep_angle.abs() / (PI / 2.)).ceil().max(1.) as usize;
+    let segment_angle = sweep_angle / segments as f32;
+    let handle_length = 4. / 3. * (segment_angle / 4.).tan();
+
+    (0..segments)
+        .map(|segment| {
+            let start = start_angle + segment_angle * segment as f32;
+            let end = start + segment_angle;
+            let to = if segment + 1 == segments {
+                to
+            } else {
+                point_at(end)
+            };
+            PathCommand::CubicBezierTo {
+                control1: point_at(start) + derivative_at(start) * handle_length,
+                control2: to - derivative_at(end) * handle_length,
+                to,
+            }
+        })
+        .collect()
+}
+
+fn rect_path_data(element: &XmlElement) -> Result<String, SvgImportError> {
+    let x = element.optional_number("x")?.unwrap_or_default();
+    let y = element.optional_number("y")?.unwrap_or_default();
+    let width = element.number("width")?;
+    let height = element.number("height")?;
+    if width < 0. || height < 0. {
+        return Err(SvgImportError::new(element.offset, "Negative size on rect"));
+    }
+
+    // A missing radius defaults to the other one
+    let rx = element.optional_number("rx")?;
+    let ry = element.optional_number("ry")?;
+    let rx = rx.or(ry).unwrap_or_default().clamp(0., width / 2.);
+    let ry = ry.or(Some(rx)).unwrap_or_default().clamp(0., height / 2.);
+
+    if rx == 0. || ry == 0. {
+        return Ok(format!("M{x} {y} H{} V{} H{x} Z", x + width, y + height));
+    }
+
+    let right = x + width;
+    let bottom = y + height;
+    Ok(format!(
+        "M{} {y} H{} A{rx} {ry} 0 0 1 {right} {} V{} A{rx} {ry} 0 0 1 {} {bottom} H{} A{rx} {ry} 0 0 1 {x} {} V{} A{rx} {ry} 0 0 1 {} {y} Z",
+        x + rx,
+        right - rx,
+        y + ry,
+        bottom - ry,
+        right - rx,
+        x + rx,
+        bottom - ry,
+        y + ry,
+        x + rx,
+    ))
+}
+
+fn ellipse_path_data(element: &XmlElement, rx: f32, ry: f32) -> Result<String, SvgImportError> {
+    let cx = element.optional_number("cx")?.unwrap_or_default();
+    let cy = element.optional_number("cy")?.unwrap_or_default();
+    Ok(format!(
+        "M{} {cy} A{rx} {ry} 0 1 0 {} {cy} A{rx} {ry} 0 1 0 {} {cy} Z",
+        cx - rx,
+        cx + rx,
+        cx - rx
+    ))
+}
+
+fn polygon_path_data(element: &XmlElement) -> Result<String, SvgImportError> {
+    let points = element.attribute("points").unwrap_or_default();
+    if points.trim().is_empty() {
+        return Ok(String::new());
+    }
+
+    // Polygon points use the same number syntax as path data, so prefixing a move is enough
+    Ok(format!("M{} Z", points))
+}
+
+struct Style {
+    fill: Option<Srgba>,
+    // The fill rule is nonzero unless set to evenodd
+    even_odd: bool,
+    stroke: Option<Srgba>,
+    stroke_width: f32,
+}
+
+impl Style {
+    fn from_element(element: &XmlElement) -> Result<Self, SvgImportError> {
+        // Properties in the style attribute take precedence over presentation attributes
+        let property = |name: &str| {
+            let from_style = element.attribute("style").and_then(|style| {
+                style.split(';').find_map(|declaration| {
+                    let (key, value) = declaration.split_once(':')?;
+                    (key.trim() == name).then_some(value.trim())
+                })
+            });
+            from_style.or_else(|| element.attribute(name))
+        };
+        let invalid = |name: &str| SvgImportError::new(element.offset, format!("Invalid {}", name));
+        // Opacities outside of 0 to 1 are clamped like in css
+        let opacity = |name: &str| -> Result<f32, SvgImportError> {
+            let Some(value) = property(name) else {
+                return Ok(1.);
+            };
+            value
+                .parse::<f32>()
+                .ok()
+                .filter(|opacity| opacity.is_finite())
+                .map(|opacity| opacity.clamp(0., 1.))
+                .ok_or_else(|| invalid(name))
+        };
+        // Colors which can't be parsed are ignored like any other invalid presentation
+        // attribute, leaving the default paint
+        let color = |name: &str, default: Option<Srgba>| {
+            property(name).and_then(parse_color).unwrap_or(default)
+        };
+
+        let element_opacity = opacity("opacity")?;
+        let with_opacity = |color: Option<Srgba>, opacity: f32| {
+            color.map(|mut color| {
+                color.alpha *= opacity;
+                color
+            })
+        };
+
+        Ok(Self {
+            fill: with_opacity(
+                color("fill", Some(Srgba::new(0., 0., 0., 1.))),
+                element_opacity * opacity("fill-opacity")?,
+            ),
+            even_odd: property("fill-rule") == Some("evenodd"),
+            stroke: with_opacity(
+                color("stroke", None),
+                element_opacity * opacity("stroke-opacity")?,
+            ),
+            stroke_width: property("stroke-width")
+                .map(|value| {
+                    parse_length(value)
+                        .filter(|width| *width >= 0.)
+                        .ok_or_else(|| invalid("stroke-width"))
+                })
+                .transpose()?
+                .unwrap_or(1.),
+        })
+    }
+}
+
+/// Parses a length with an optional px unit, which is the only unit supported. Rust parses NaN
+/// and inf as well, which svg doesn't allow.
+fn parse_length(value: &str) -> Option<f32> {
+    let value = value.trim();
+    value
+        .strip_suffix("px")
+        .unwrap_or(value)
+        .parse()
+        .ok()
+        .filter(|length: &f32| length.is_finite())
+}
+
+/// Parses an svg color. Returns `Some(None)` for `none`. `currentColor` is black, the initial
+/// value of `color`, since css isn't applied.
+fn parse_color(value: &str) -> Option<Option<Srgba>> {
+    let value = value.trim();
+    let rgba = |red: u8, green: u8, blue: u8, alpha: f32| {
+        Some(Some(Srgba::new(
+            red as f32 / 255.,
+            green as f32 / 255.,
+            blue as f32 / 255.,
+            alpha,
+        )))
+    };
+    let rgb = |red: u8, green: u8, blue: u8| rgba(red, green, blue, 1.);
+
+    if let Some(hex) = value.strip_prefix('#') {
+        let digit = |index: usize| u8::from_str_radix(hex.get(index..index + 1)?, 16).ok();
+        let byte = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+        return match hex.len() {
+            3 => rgb(digit(0)? * 17, digit(1)? * 17, digit(2)? * 17),
+            4 => rgba(
+                digit(0)? * 17,
+                digit(1)? * 17,
+                digit(2)? * 17,
+                (digit(3)? * 17) as f32 / 255.,
+            ),
+            6 => rgb(byte(0)?, byte(2)?, byte(4)?),
+            8 => rgba(byte(0)?, byte(2)?, byte(4)?, byte(6)? as f32 / 255.),
+            _ => None,
+        };
+    }
+
+    if let Some((function, arguments)) = value
+        .strip_suffix(')')
+        .and_then(|value| value.split_once('('))
+    {
+        let arguments: Vec<_> = arguments.split(',').map(str::trim).collect();
+        let channel = |index: usize| arguments[index].parse::<u8>().ok();
+        return match (function.trim(), arguments.len()) {
+            ("rgb", 3) => rgb(channel(0)?, channel(1)?, channel(2)?),
+            ("rgba", 4) => rgba(
+                channel(0)?,
+                channel(1)?,
+                channel(2)?,
+                arguments[3].parse::<f32>().ok()?.clamp(0., 1.),
+            ),
+            _ => None,
+        };
+    }
+
+    match value.to_ascii_lowercase().as_str() {
+        "none" | "transparent" => Some(None),
+        "black" | "currentcolor" => rgb(0, 0, 0),
+        "white" => rgb(255, 255, 255),
+        "red" => rgb(255, 0, 0),
+        "green" => rgb(0, 128, 0),
+        "lime" => rgb(0, 255, 0),
+        "blue" => rgb(0, 0, 255),
+        "navy" => rgb(0, 0, 128),
+        "yellow" => rgb(255, 255, 0),
+        "cyan" | "aqua" => rgb(0, 255, 255),
+        "magenta" | "fuchsia" => rgb(255, 0, 255),
+        "gray" | "grey" => rgb(128, 128, 128),
+        "silver" => rgb(192, 192, 192),
+        "maroon" => rgb(128, 0, 0),
+        "olive" => rgb(128, 128, 0),
+        "purple" => rgb(128, 0, 128),
+        "teal" => rgb(0, 128, 128),
+        "orange" => rgb(255, 165, 0),
+        _ => None,
+    }
+}
+
+struct XmlElement<'a> {
+    name: &'a str,
+    offset: usize,
+    attributes: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> XmlElement<'a> {
+    fn attribute(&self, name: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+    }
+
+    fn optional_number(&self, name: &str) -> Result<Option<f32>, SvgImportError> {
+        self.attribute(name)
+            .map(|value| {
+                parse_length(value).ok_or_else(|| {
+                    SvgImportError::new(
+                        self.offset,
+                        format!("Invalid {} attribute on {}", name, self.name),
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    fn number(&self, name: &str) -> Result<f32, SvgImportError> {
+        self.optional_number(name)?.ok_or_else(|| {
+            SvgImportError::new(
+                self.offset,
+                format!("Missing {} attribute on {}", name, self.name),
+            )
+        })
+    }
+}
+
+/// A minimal scanner over the start tags of an xml document. Comments, processing instructions,
+/// doctypes, end tags and text are skipped, and entity references in attribute values are left
+/// as is since the attributes read are all numbers, colors or path data.
+struct XmlElements<'a> {
+    document: &'a str,
+    position: usize,
+}
+
+impl<'a> XmlElements<'a> {
+    fn new(document: &'a str) -> Self {
+        Self {
+            document,
+            position: 0,
+        }
+    }
+
+    fn skip_past(&mut self, terminator: &str) -> Result<(), SvgImportError> {
+        match self.document[self.position..].find(terminator) {
+            Some(index) => {
+                self.position += index + terminator.len();
+                Ok(())
+            }
+            None => Err(SvgImportError::new(
+                self.position,
+                format!("Missing '{}'", terminator),
+            )),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.document[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn name(&mut self) -> &'a str {
+        let document = self.document;
+        let rest = &document[self.position..];
+        let length = rest
+            .find(|character: char| {
+                character.is_whitespace() || matches!(character, '=' | '/' | '>')
+            })
+            .unwrap_or(rest.len());
+        self.position += length;
+        &rest[..length]
+    }
+
+    fn element(&mut self) -> Result<XmlElement<'a>, SvgImportError> {
+        let offset = self.position;
+        self.position += 1;
+        let name = self.name();
+        let mut attributes = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            let rest = &self.document[self.position..];
+            if rest.starts_with("/>") {
+                self.position += 2;
+                break;
+            } else if rest.starts_with('>') {
+                self.position += 1;
+                break;
+            } else if rest.is_empty() {
+                return Err(SvgImportError::new(offset, "Unterminated element"));
+            }
+
+            let key = self.name();
+            if key.is_empty() {
+                return Err(SvgImportError::new(self.position, "Expected an attribute"));
+            }
+            self.skip_whitespace();
+            if !self.document[self.position..].starts_with('=') {
+                return Err(SvgImportError::new(self.position, "Expected '='"));
+            }
+            self.position += 1;
+            self.skip_whitespace();
+
+            let quote = match self.document[self.position..].chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => return Err(SvgImportError::new(self.position, "Expected a quote")),
+            };
+            self.position += 1;
+            let start = self.position;
+            let length = self.document[start..]
+                .find(quote)
+                .ok_or_else(|| SvgImportError::new(start, "Unterminated attribute value"))?;
+            let document = self.document;
+            attributes.push((key, &document[start..start + length]));
+            self.position = start + length + 1;
+        }
+
+        Ok(XmlElement {
+            name,
+            offset,
+            attributes,
+        })
+    }
+}
+
+impl<'a> Iterator for XmlElements<'a> {
+    type Item = Result<XmlElement<'a>, SvgImportError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let index = self.document[self.position..].find('<')?;
+            self.position += index;
+            let rest = &self.document[self.position..];
+
+            let skipped = if rest.starts_with("<!--") {
+                self.skip_past("-->")
+            } else if rest.starts_with("<![CDATA[") {
+                self.skip_past("]]>")
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")
+            } else if rest.starts_with("<!") || rest.starts_with("</") {
+                self.skip_past(">")
+            } else {
+                let element = self.element();
+                if element.is_err() {
+                    // Stop after the first error
+                    self.position = self.document.len();
+                }
+                return Some(element);
+            };
+
+            if let Err(error) = skipped {
+                self.position = self.document.len();
+                return Some(Err(error));
+            }
+        }
+    }
+}
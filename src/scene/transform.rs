@@ -0,0 +1,93 @@
+use glam::{Vec2, Vec4};
+use glamour::{Point2, Vector2};
+use serde::{Deserialize, Serialize};
+
+/// A 2D affine transform mapping `(x, y)` to `(a * x + c * y + e, b * x + d * y + f)`, laid out
+/// like the svg and css `matrix(a, b, c, d, e, f)` function. Angles are in radians, and positive
+/// angles rotate clockwise on screen since the y axis points down.
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self::new(1., 0., 0., 1., 0., 0.);
+
+    pub const fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    pub fn translation(offset: Vector2) -> Self {
+        Self::new(1., 0., 0., 1., offset.x, offset.y)
+    }
+
+    pub fn scale(x: f32, y: f32) -> Self {
+        Self::new(x, 0., 0., y, 0., 0.)
+    }
+
+    pub fn rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0., 0.)
+    }
+
+    /// Skews along the x axis by `x` and along the y axis by `y`, like css `skew(x, y)`.
+    pub fn skew(x: f32, y: f32) -> Self {
+        Self::new(1., y.tan(), x.tan(), 1., 0., 0.)
+    }
+
+    /// Returns the transform which applies this one and then `other`.
+    pub fn then(&self, other: &Transform) -> Self {
+        Self::new(
+            other.a * self.a + other.c * self.b,
+            other.b * self.a + other.d * self.b,
+            other.a * self.c + other.c * self.d,
+            other.b * self.c + other.d * self.d,
+            other.a * self.e + other.c * self.f + other.e,
+            other.b * self.e + other.d * self.f + other.f,
+        )
+    }
+
+    /// Returns this transform applied around `origin` instead of around (0, 0), which is usually
+    /// the center of the primitive for rotations and scales.
+    pub fn around(&self, origin: Point2) -> Self {
+        Self::translation(Vector2::new(-origin.x, -origin.y))
+            .then(self)
+            .then(&Self::translation(Vector2::new(origin.x, origin.y)))
+    }
+
+    pub fn apply(&self, point: Point2) -> Point2 {
+        Point2::new(
+            self.a * point.x + self.c * point.y + self.e,
+            self.b * point.x + self.d * point.y + self.f,
+        )
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    /// The matrix as `[a, b, c, d, e, f]`.
+    pub fn to_array(&self) -> [f32; 6] {
+        [self.a, self.b, self.c, self.d, self.e, self.f]
+    }
+
+    // The columns of the linear part as uploaded to the shaders
+    pub(crate) fn matrix_columns(&self) -> Vec4 {
+        Vec4::new(self.a, self.b, self.c, self.d)
+    }
+
+    pub(crate) fn translation_vector(&self) -> Vec2 {
+        Vec2::new(self.e, self.f)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
@@ -0,0 +1,71 @@
+use glam::Vec4;
+use glamour::{AsRaw, Point2, Rect, Size2};
+use palette::Srgba;
+use serde::{Deserialize, Serialize};
+
+use crate::default_drawables::InstancedUndercurl;
+
+/// A horizontal wavy line like the ones used for spelling and diagnostic undercurls, evaluated per
+/// pixel in the shader. The wave's phase is taken from the absolute x position rather than from
+/// `start`, so undercurls drawn per cell in adjacent cells join into one seamless wave.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Undercurl {
+    /// The left end of the wave's center line.
+    pub start: Point2,
+    pub length: f32,
+    pub thickness: f32,
+    /// The horizontal distance between two crests.
+    pub wavelength: f32,
+    /// How far the wave moves above and below its center line.
+    pub amplitude: f32,
+    pub color: Srgba,
+}
+
+impl Undercurl {
+    pub fn new(
+        start: Point2,
+        length: f32,
+        thickness: f32,
+        wavelength: f32,
+        amplitude: f32,
+        color: Srgba,
+    ) -> Self {
+        Self {
+            start,
+            length,
+            thickness,
+            wavelength,
+            amplitude,
+            color,
+        }
+    }
+
+    /// The area covered by the wave including its thickness.
+    pub fn bounds(&self) -> Rect {
+        let extent = self.amplitude.abs() + self.thickness.max(0.) / 2.;
+        Rect::new(
+            Point2::new(self.start.x, self.start.y - extent),
+            Size2::new(self.length.max(0.), extent * 2.),
+        )
+    }
+
+    /// The height of the wave's center line at `x`.
+    pub fn offset_at(&self, x: f32) -> f32 {
+        if self.wavelength <= 0. {
+            return 0.;
+        }
+        self.amplitude * (x / self.wavelength * std::f32::consts::TAU).sin()
+    }
+
+    pub fn to_instanced(&self) -> InstancedUndercurl {
+        InstancedUndercurl {
+            color: Vec4::from_array(self.color.into_linear().into()),
+            start: *self.start.as_raw(),
+            length: self.length.max(0.),
+            thickness: self.thickness.max(0.),
+            wavelength: self.wavelength,
+            amplitude: self.amplitude,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use std::fmt::{self, Display};
+
+use glamour::{Point2, Rect, Size2};
+use palette::Srgba;
+
+use super::{
+    Blur, Ellipse, FontId, GlyphRun, Gradient, GradientShape, Layer, LayerContents, LineSegment,
+    MutablePrimitiveBatch, Path, PathCommand, PrimitiveBatch, PrimitiveKind, Quad, Resources,
+    Scene, Shadow, Sprite, TextureId, Transform, Undercurl, MAX_GRADIENT_STOPS,
+};
+
+/// A primitive which can't be drawn as reported by `Scene::validate`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneProblem {
+    pub layer: usize,
+    /// Whether the primitive is part of the layer's mask rather than its contents.
+    pub mask: bool,
+    pub batch: usize,
+    /// The index of the primitive within its batch.
+    pub primitive: usize,
+    pub kind: SceneProblemKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneProblemKind {
+    /// A glyph run refers to a font which isn't in the scene resources.
+    MissingFont(FontId),
+    /// A glyph run refers to a font index the font data doesn't contain.
+    InvalidFontIndex { font: FontId, index: usize },
+    /// A sprite refers to a texture which isn't in the scene resources.
+    MissingTexture(TextureId),
+    /// A sprite refers to a texture whose data doesn't match its size.
+    InvalidTexture(TextureId),
+    /// A position, size, color or other value of the primitive is NaN or infinite.
+    NonFiniteValue,
+    /// A path which isn't closed has a fill.
+    OpenPathWithFill,
+    /// A quad gradient has more than `MAX_GRADIENT_STOPS` stops.
+    TooManyGradientStops(usize),
+}
+
+impl Scene {
+    /// Checks every primitive in the scene for problems which would stop it from rendering, such
+    /// as references to missing resources or NaN coordinates. An empty list means the scene is
+    /// safe to render as is.
+    pub fn validate(&self) -> Vec<SceneProblem> {
+        let mut problems = Vec::new();
+        for (layer_index, layer) in self.layers.iter().enumerate() {
+            let mut report = |mask: bool, contents: &LayerContents| {
+                for (batch_index, batch) in contents.primitives.iter().enumerate() {
+                    for (primitive, kind) in batch_problems(batch, &self.resources) {
+                        problems.push(SceneProblem {
+                            layer: layer_index,
+                            mask,
+                            batch: batch_index,
+                            primitive,
+                            kind,
+                        });
+                    }
+                }
+            };
+
+            if let Some(mask) = &layer.mask {
+                report(true, mask);
+            }
+            report(false, &layer.contents);
+        }
+
+        problems
+    }
+
+    /// Returns a copy of the scene with the primitives named in `problems` removed. Batches which
+    /// lose primitives are turned into mutable batches, and everything else is shared with this
+    /// scene.
+    pub fn without_problems(&self, problems: &[SceneProblem]) -> Scene {
+        Scene {
+            layers: self.layers_without_problems(problems),
+            resources: self.resources.clone(),
+        }
+    }
+
+    /// Like `without_problems`, but only copies the layers so the renderer can draw them with the
+    /// existing resources.
+    pub(crate) fn layers_without_problems(&self, problems: &[SceneProblem]) -> Vec<Layer> {
+        let mut layers = self.layers.clone();
+        for (layer_index, layer) in layers.iter_mut().enumerate() {
+            let mut remove = |mask: bool, contents: &mut LayerContents| {
+                for (batch_index, batch) in contents.primitives.iter_mut().enumerate() {
+                    let invalid: Vec<usize> = problems
+                        .iter()
+                        .filter(|problem| {
+                            problem.layer == layer_index
+                                && problem.mask == mask
+                                && problem.batch == batch_index
+                        })
+                        .map(|problem| problem.primitive)
+                        .collect();
+                    if !invalid.is_empty() {
+                        *batch = without_primitives(batch, &invalid);
+                    }
+                }
+            };
+
+            if let Some(mask) = &mut layer.mask {
+                remove(true, mask);
+            }
+            remove(false, &mut layer.contents);
+        }
+
+        layers
+    }
+}
+
+fn batch_problems(batch: &PrimitiveBatch, resources: &Resources) -> Vec<(usize, SceneProblemKind)> {
+    fn check<T>(
+        primitives: &[T],
+        check: impl Fn(&T) -> Option<SceneProblemKind>,
+    ) -> Vec<(usize, SceneProblemKind)> {
+        primitives
+            .iter()
+            .enumerate()
+            .filter_map(|(index, primitive)| Some((index, check(primitive)?)))
+            .collect()
+    }
+
+    match batch.kind() {
+        PrimitiveKind::Blurs => check(batch.as_blur_vec().unwrap(), blur_problem),
+        PrimitiveKind::Quads => check(batch.as_quad_vec().unwrap(), quad_problem),
+        PrimitiveKind::GlyphRuns => check(batch.as_glyph_run_vec().unwrap(), |glyph_run| {
+            glyph_run_problem(glyph_run, resources)
+        }),
+        PrimitiveKind::Paths => check(batch.as_path_vec().unwrap(), path_problem),
+        PrimitiveKind::Sprites => check(batch.as_sprite_vec().unwrap(), |sprite| {
+            sprite_problem(sprite, resources)
+        }),
+        PrimitiveKind::Shadows => check(batch.as_shadow_vec().unwrap(), shadow_problem),
+        PrimitiveKind::Ellipses => check(batch.as_ellipse_vec().unwrap(), ellipse_problem),
+        PrimitiveKind::LineSegments => {
+            check(batch.as_line_segment_vec().unwrap(), line_segment_problem)
+        }
+        PrimitiveKind::Undercurls => check(batch.as_undercurl_vec().unwrap(), undercurl_problem),
+    }
+}
+
+fn blur_problem(blur: &Blur) -> Option<SceneProblemKind> {
+    let finite = point_is_finite(blur.top_left)
+        && size_is_finite(blur.size)
+        && color_is_finite(blur.color)
+        && blur.corner_radius.is_finite()
+        && blur.blur.is_finite();
+    (!finite).then_some(SceneProblemKind::NonFiniteValue)
+}
+
+fn quad_problem(quad: &Quad) -> Option<SceneProblemKind> {
+    if let Some(gradient) = &quad.gradient {
+        if gradient.stops.len() > MAX_GRADIENT_STOPS {
+            return Some(SceneProblemKind::TooManyGradientStops(gradient.stops.len()));
+        }
+    }
+
+    let finite = rect_is_finite(quad.region)
+        && color_is_finite(quad.color)
+        && quad
+            .corner_radius
+            .to_array()
+            .iter()
+            .all(|radius| radius.is_finite())
+        && quad.edge_blur.is_finite()
+        && quad.border.iter().all(|border| {
+            border
+                .widths
+                .to_array()
+                .iter()
+                .all(|width| width.is_finite())
+                && border
+                    .colors
+                    .to_array()
+                    .iter()
+                    .all(|color| color_is_finite(*color))
+                && border.dash.iter().all(|dash| {
+                    dash.on.is_finite() && dash.off.is_finite() && dash.phase.is_finite()
+                })
+        })
+        && quad.gradient.iter().all(gradient_is_finite)
+        && quad.transform.iter().all(transform_is_finite);
+    (!finite).then_some(SceneProblemKind::NonFiniteValue)
+}
+
+fn shadow_problem(shadow: &Shadow) -> Option<SceneProblemKind> {
+    let finite = rect_is_finite(shadow.region)
+        && color_is_finite(shadow.color)
+        && shadow
+            .corner_radius
+            .to_array()
+            .iter()
+            .all(|radius| radius.is_finite())
+        && shadow.offset.x.is_finite()
+        && shadow.offset.y.is_finite()
+        && shadow.blur.is_finite()
+        && shadow.spread.is_finite();
+    (!finite).then_some(SceneProblemKind::NonFiniteValue)
+}
+
+fn ellipse_problem(ellipse: &Ellipse) -> Option<SceneProblemKind> {
+    let finite = point_is_finite(ellipse.center)
+        && ellipse.radii.x.is_finite()
+        && ellipse.radii.y.is_finite()
+        && color_is_finite(ellipse.color)
+        && ellipse.stroke_width.is_finite()
+        && color_is_finite(ellipse.stroke_color);
+    (!finite).then_some(SceneProblemKind::NonFiniteValue)
+}
+
+fn line_segment_problem(segment: &LineSegment) -> Option<SceneProblemKind> {
+    let finite = point_is_finite(segment.start)
+        && point_is_finite(segment.end)
+        && segment.width.is_finite()
+        && color_is_finite(segment.color);
+    (!finite).then_some(SceneProblemKind::NonFiniteValue)
+}
+
+fn undercurl_problem(undercurl: &Undercurl) -> Option<SceneProblemKind> {
+    let finite = point_is_finite(undercurl.start)
+        && undercurl.length.is_finite()
+        && undercurl.thickness.is_finite()
+        && undercurl.wavelength.is_finite()
+        && undercurl.amplitude.is_finite()
+        && color_is_finite(undercurl.color);
+    (!finite).then_some(SceneProblemKind::NonFiniteValue)
+}
+
+fn gradient_is_finite(gradient: &Gradient) -> bool {
+    let shape_is_finite = match gradient.shape {
+        GradientShape::Linear { angle } => angle.is_finite(),
+        GradientShape::Radial { center, radius } => point_is_finite(center) && radius.is_finite(),
+    };
+    shape_is_finite
+        && gradient
+            .stops
+            .iter()
+            .all(|stop| stop.offset.is_finite() && color_is_finite(stop.color))
+}
+
+fn glyph_run_problem(glyph_run: &GlyphRun, resources: &Resources) -> Option<SceneProblemKind> {
+    let Some(font) = resources.fonts.get(&glyph_run.font_id) else {
+        return Some(SceneProblemKind::MissingFont(glyph_run.font_id));
+    };
+    if font.as_swash_font_ref(glyph_run.font_index).is_none() {
+        return Some(SceneProblemKind::InvalidFontIndex {
+            font: glyph_run.font_id,
+            index: glyph_run.font_index,
+        });
+    }
+
+    let finite = point_is_finite(glyph_run.position)
+        && color_is_finite(glyph_run.color)
+        && glyph_run.size.is_finite()
+        && glyph_run
+            .glyphs
+            .iter()
+            .all(|glyph| glyph.offset.x.is_finite() && glyph.offset.y.is_finite());
+    (!finite).then_some(SceneProblemKind::NonFiniteValue)
+}
+
+fn path_problem(path: &Path) -> Option<SceneProblemKind> {
+    if path.open && path.fill.is_some() {
+        return Some(SceneProblemKind::OpenPathWithFill);
+    }
+
+    let finite = point_is_finite(path.start)
+        && path.fill.iter().all(|fill| color_is_finite(*fill))
+        && path
+            .stroke
+            .iter()
+            .all(|(width, color)| width.is_finite() && color_is_finite(*color))
+        && path.stroke_style.iter().all(|style| {
+            style.miter_limit.is_finite()
+                && style.dash_array.iter().all(|length| length.is_finite())
+                && style.dash_offset.is_finite()
+        })
+        && path.commands.iter().all(|command| match command {
+            PathCommand::CubicBezierTo {
+                control1,
+                control2,
+                to,
+            } => point_is_finite(*control1) && point_is_finite(*control2) && point_is_finite(*to),
+            PathCommand::QuadraticBezierTo { control, to } => {
+                point_is_finite(*control) && point_is_finite(*to)
+            }
+            PathCommand::LineTo { to } => point_is_finite(*to),
+        });
+    (!finite).then_some(SceneProblemKind::NonFiniteValue)
+}
+
+fn sprite_problem(sprite: &Sprite<TextureId>, resources: &Resources) -> Option<SceneProblemKind> {
+    let Some(texture) = resources.textures.get(&sprite.texture) else {
+        return Some(SceneProblemKind::MissingTexture(sprite.texture));
+    };
+    let expected_length = texture.size.width as usize * texture.size.height as usize * 4;
+    if texture.data.len() != expected_length {
+        return Some(SceneProblemKind::InvalidTexture(sprite.texture));
+    }
+
+    let finite = point_is_finite(sprite.top_left)
+        && size_is_finite(sprite.size)
+        && color_is_finite(sprite.color)
+        && sprite.transform.iter().all(transform_is_finite);
+    (!finite).then_some(SceneProblemKind::NonFiniteValue)
+}
+
+fn point_is_finite(point: Point2) -> bool {
+    point.x.is_finite() && point.y.is_finite()
+}
+
+fn size_is_finite(size: Size2) -> bool {
+    size.width.is_finite() && size.height.is_finite()
+}
+
+fn transform_is_finite(transform: &Transform) -> bool {
+    transform.to_array().iter().all(|value| value.is_finite())
+}
+
+fn rect_is_finite(rect: Rect) -> bool {
+    point_is_finite(rect.origin) && size_is_finite(rect.size)
+}
+
+fn color_is_finite(color: Srgba) -> bool {
+    color.red.is_finite()
+        && color.green.is_finite()
+        && color.blue.is_finite()
+        && color.alpha.is_finite()
+}
+
+fn without_primitives(batch: &PrimitiveBatch, invalid: &[usize]) -> PrimitiveBatch {
+    fn keep<T: Clone>(primitives: &[T], invalid: &[usize]) -> Vec<T> {
+        primitives
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !invalid.contains(index))
+            .map(|(_, primitive)| primitive.clone())
+            .collect()
+    }
+
+    PrimitiveBatch::Mutable(match batch.kind() {
+        PrimitiveKind::Blurs => {
+            MutablePrimitiveBatch::Blurs(keep(batch.as_blur_vec().unwrap(), invalid))
+        }
+        PrimitiveKind::Quads => {
+            MutablePrimitiveBatch::Quads(keep(batch.as_quad_vec().unwrap(), invalid))
+        }
+        PrimitiveKind::GlyphRuns => {
+            MutablePrimitiveBatch::GlyphRuns(keep(batch.as_glyph_run_vec().unwrap(), invalid))
+        }
+        PrimitiveKind::Paths => {
+            MutablePrimitiveBatch::Paths(keep(batch.as_path_vec().unwrap(), invalid))
+        }
+        PrimitiveKind::Sprites => {
+            MutablePrimitiveBatch::Sprites(keep(batch.as_sprite_vec().unwrap(), invalid))
+        }
+        PrimitiveKind::Shadows => {
+            MutablePrimitiveBatch::Shadows(keep(batch.as_shadow_vec().unwrap(), invalid))
+        }
+        PrimitiveKind::Ellipses => {
+            MutablePrimitiveBatch::Ellipses(keep(batch.as_ellipse_vec().unwrap(), invalid))
+        }
+        PrimitiveKind::LineSegments => {
+            MutablePrimitiveBatch::LineSegments(keep(batch.as_line_segment_vec().unwrap(), invalid))
+        }
+        PrimitiveKind::Undercurls => {
+            MutablePrimitiveBatch::Undercurls(keep(batch.as_undercurl_vec().unwrap(), invalid))
+        }
+    })
+}
+
+impl Display for SceneProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Layer {}", self.layer)?;
+        if self.mask {
+            write!(f, " mask")?;
+        }
+        write!(
+            f,
+            " batch {} primitive {}: {}",
+            self.batch, self.primitive, self.kind
+        )
+    }
+}
+
+impl Display for SceneProblemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneProblemKind::MissingFont(font) => {
+                write!(f, "font {} is not in the scene resources", font.raw())
+            }
+            SceneProblemKind::InvalidFontIndex { font, index } => {
+                write!(f, "font {} has no font at index {}", font.raw(), index)
+            }
+            SceneProblemKind::MissingTexture(texture) => {
+                write!(f, "texture {} is not in the scene resources", texture.raw())
+            }
+            SceneProblemKind::InvalidTexture(texture) => {
+                write!(f, "texture {} data does not match its size", texture.raw())
+            }
+            SceneProblemKind::NonFiniteValue => write!(f, "contains a NaN or infinite value"),
+            SceneProblemKind::OpenPathWithFill => write!(f, "open path has a fill"),
+            SceneProblemKind::TooManyGradientStops(count) => write!(
+                f,
+                "gradient has {} stops but at most {} are supported",
+                count, MAX_GRADIENT_STOPS
+            ),
+        }
+    }
+}
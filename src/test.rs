@@ -1,19 +1,32 @@
 mod font_styles;
+mod path_dashes;
+mod scene_diff;
+mod scene_format;
+mod scene_schema;
+mod scene_stats;
+mod scene_validation;
+mod svg_export;
+mod svg_import;
 
-use std::{env::temp_dir, fs::create_dir_all, path::PathBuf, thread};
+use std::{env::temp_dir, fs::create_dir_all, path::PathBuf, sync::Arc, thread};
 
 use glamour::{point2, size2, vec2, Rect};
 use image::ImageReader;
 use lazy_static::lazy_static;
 use palette::Srgba;
 use parley::{
+    layout::editor::{PlainEditor, PlainEditorOp},
     style::{FontFamily, FontSettings, FontStack, FontWeight, StyleProperty},
     swash::Setting,
+    FontContext, LayoutContext,
 };
 use rust_embed::RustEmbed;
 
 use crate::{
-    offscreen_renderer::OffscreenRenderer, scene::Scene, Layer, Path, Quad, Shaper, Sprite, Texture,
+    offscreen_renderer::OffscreenRenderer, scene::Scene, BorderColors, BorderDash, BorderWidths,
+    CornerRadii, EditorStyle, Ellipse, Gradient, GradientInterpolation, GradientStop, Layer,
+    LineCap, LineJoin, LineSegment, Path, Quad, Shadow, Shaper, Sprite, StrokeStyle, Texture,
+    TextureId, Transform, Undercurl,
 };
 
 #[derive(RustEmbed)]
@@ -118,6 +131,111 @@ fn simple_text() {
     assert_no_regressions(1000, 500, scene);
 }
 
+#[test]
+fn text_decorations() {
+    let mut scene = Scene::new().with_clear(Srgba::new(1., 1., 1., 1.));
+    let mut shaper = Shaper::new();
+    shaper.push_default(StyleProperty::FontStack(FontStack::Source("monospace")));
+    shaper.push_default(StyleProperty::Brush(Srgba::new(0., 0., 0., 1.)));
+
+    let red = Srgba::new(1., 0., 0., 1.);
+    let text = "Underlined glyphs, struck through";
+    let layout = shaper.layout_with(text, |builder| {
+        builder.push_default(&StyleProperty::FontSize(20.));
+        builder.push(&StyleProperty::Underline(true), 0..17);
+        builder.push(&StyleProperty::UnderlineBrush(Some(red)), 11..17);
+        builder.push(&StyleProperty::Strikethrough(true), 19..text.len());
+    });
+    scene.add_text_layout(&layout, point2!(10., 10.));
+
+    let segments: Vec<_> = scene
+        .layer()
+        .contents
+        .primitives
+        .iter()
+        .filter_map(|batch| batch.as_line_segment_vec())
+        .flatten()
+        .collect();
+    assert!(segments.iter().any(|segment| segment.color == red));
+    // The underline is broken around the descenders of "g", "y" and "p"
+    assert!(segments.len() > 3);
+
+    assert_no_regressions(400, 50, scene);
+}
+
+#[test]
+fn editor_selection_caret_and_preedit() {
+    let mut font_context = FontContext::default();
+    let mut layout_context = LayoutContext::new();
+    let mut editor = PlainEditor::default();
+    editor.transact(
+        &mut font_context,
+        &mut layout_context,
+        [
+            PlainEditorOp::SetDefaultStyle(Arc::new([
+                StyleProperty::FontStack(FontStack::Source("monospace")),
+                StyleProperty::FontSize(20.),
+                StyleProperty::Brush(Srgba::new(0., 0., 0., 1.)),
+            ])),
+            PlainEditorOp::SetWidth(380.),
+            PlainEditorOp::SetText("Sphinx of black quartz".into()),
+            PlainEditorOp::MoveToPoint(80., 10.),
+            PlainEditorOp::ExtendSelectionToPoint(180., 10.),
+        ],
+    );
+
+    // The preedit covers "quartz" at the end of the text
+    let style = EditorStyle::new()
+        .with_selection_color(Srgba::new(1., 0.8, 0., 0.5))
+        .with_caret(2., Srgba::new(1., 0., 0., 1.))
+        .with_preedit(16..22);
+    let mut scene = Scene::new().with_clear(Srgba::new(1., 1., 1., 1.));
+    scene.update_layer(|resources, layer| {
+        layer.add_styled_editor(resources, editor, &style, point2!(10., 10.));
+    });
+
+    let quads = scene
+        .layer()
+        .contents
+        .primitives
+        .iter()
+        .filter_map(|batch| batch.as_quad_vec())
+        .flatten()
+        .count();
+    // At least one selection rectangle and the caret
+    assert!(quads >= 2);
+    assert!(scene
+        .layer()
+        .contents
+        .primitives
+        .iter()
+        .any(|batch| batch.is_line_segments()));
+
+    assert_no_regressions(400, 50, scene);
+}
+
+#[test]
+fn missing_resources_are_skipped_when_drawing() {
+    let mut scene: Scene = serde_json::from_str(include_str!("../scene.json")).unwrap();
+    scene.resources.fonts.clear();
+    scene.layer_mut().add_sprite(Sprite::new(
+        TextureId::new(42),
+        point2!(0., 0.),
+        size2!(10., 10.),
+    ));
+
+    for validate in [false, true] {
+        smol::block_on(async {
+            let mut renderer = OffscreenRenderer::new(100, 100)
+                .await
+                .with_default_drawables()
+                .await
+                .with_scene_validation(validate);
+            renderer.draw(&scene).await
+        });
+    }
+}
+
 #[test]
 fn simple_path() {
     let scene = Scene::new().with_path(
@@ -195,6 +313,294 @@ fn simple_blurred_quad() {
     assert_no_regressions(325, 325, scene);
 }
 
+#[test]
+fn quad_corner_radii() {
+    let scene = Scene::new()
+        .with_clear(Srgba::new(1., 1., 1., 1.))
+        .with_quad(
+            Quad::new(
+                Rect::new(point2!(10., 10.), size2!(100., 50.)),
+                Srgba::new(0., 0., 1., 1.),
+            )
+            .with_corner_radius(CornerRadii::new(20., 20., 0., 0.)),
+        )
+        .with_quad(
+            Quad::new(
+                Rect::new(point2!(10., 70.), size2!(100., 50.)),
+                Srgba::new(1., 0., 0., 1.),
+            )
+            .with_corner_radius(CornerRadii::new(0., 10., 25., 5.))
+            .with_edge_blur(2.),
+        );
+
+    assert_no_regressions(120, 130, scene);
+}
+
+#[test]
+fn quad_borders() {
+    let scene = Scene::new()
+        .with_clear(Srgba::new(1., 1., 1., 1.))
+        .with_quad(
+            Quad::new(
+                Rect::new(point2!(10., 10.), size2!(100., 50.)),
+                Srgba::new(0.8, 0.8, 1., 1.),
+            )
+            .with_corner_radius(10.)
+            .with_border(4., Srgba::new(0., 0., 1., 1.)),
+        )
+        .with_quad(
+            Quad::new(
+                Rect::new(point2!(10., 70.), size2!(100., 50.)),
+                Srgba::new(1., 1., 0., 0.5),
+            )
+            .with_corner_radius(CornerRadii::new(0., 15., 25., 5.))
+            .with_border(
+                BorderWidths::new(2., 6., 10., 4.),
+                BorderColors::new(
+                    Srgba::new(1., 0., 0., 1.),
+                    Srgba::new(0., 1., 0., 1.),
+                    Srgba::new(0., 0., 1., 1.),
+                    Srgba::new(0., 0., 0., 0.5),
+                ),
+            )
+            .with_edge_blur(1.),
+        );
+
+    assert_no_regressions(120, 130, scene);
+}
+
+#[test]
+fn quad_border_dashes() {
+    let black = Srgba::new(0., 0., 0., 1.);
+    let quad = |y: f32| {
+        Quad::new(
+            Rect::new(point2!(10., y), size2!(100., 40.)),
+            Srgba::new(1., 1., 1., 0.),
+        )
+    };
+    let scene = Scene::new()
+        .with_clear(Srgba::new(1., 1., 1., 1.))
+        .with_quad(
+            quad(10.)
+                .with_corner_radius(10.)
+                .with_border(2., black)
+                .with_border_dash(BorderDash::new(6., 4.)),
+        )
+        .with_quad(
+            quad(60.)
+                .with_corner_radius(CornerRadii::new(0., 20., 0., 20.))
+                .with_border(4., Srgba::new(0., 0., 1., 1.))
+                .with_border_dash(BorderDash::dotted(8.)),
+        )
+        .with_quad(
+            quad(110.)
+                .with_border(3., Srgba::new(1., 0., 0., 1.))
+                .with_border_dash(
+                    BorderDash::new(4., 8.)
+                        .with_cap(LineCap::Square)
+                        .with_phase(3.),
+                ),
+        );
+
+    assert_no_regressions(120, 160, scene);
+}
+
+#[test]
+fn transformed_quads_and_sprites() {
+    let image_file = Assets::get("Leaf.png").unwrap();
+    let image = image::load_from_memory(image_file.data.as_ref()).unwrap();
+    let texture = Texture::from_image(image);
+
+    let scene = Scene::new()
+        .with_clear(Srgba::new(1., 1., 1., 1.))
+        .with_quad(
+            Quad::new(
+                Rect::new(point2!(20., 20.), size2!(60., 40.)),
+                Srgba::new(1., 0., 0., 1.),
+            )
+            .with_corner_radius(8.)
+            .with_border(3., Srgba::new(0., 0., 0., 1.))
+            .with_transform(Transform::rotation(0.5).around(point2!(50., 40.))),
+        )
+        .with_quad(
+            Quad::new(
+                Rect::new(point2!(20., 100.), size2!(60., 40.)),
+                Srgba::new(0., 0., 1., 1.),
+            )
+            .with_transform(Transform::skew(0.4, 0.).around(point2!(50., 120.))),
+        )
+        .with_sprite(
+            Sprite::new(texture, point2!(110., 40.), size2!(60., 60.))
+                .with_transform(Transform::rotation(-0.8).around(point2!(140., 70.))),
+        );
+
+    assert_no_regressions(200, 160, scene);
+}
+
+#[test]
+fn quad_gradients() {
+    let red = Srgba::new(1., 0., 0., 1.);
+    let blue = Srgba::new(0., 0., 1., 1.);
+    let scene = Scene::new()
+        .with_clear(Srgba::new(1., 1., 1., 1.))
+        .with_quad(
+            Quad::new(Rect::new(point2!(10., 10.), size2!(100., 30.)), red).with_gradient(
+                Gradient::linear(
+                    90.,
+                    [GradientStop::new(0., red), GradientStop::new(1., blue)],
+                ),
+            ),
+        )
+        .with_quad(
+            Quad::new(Rect::new(point2!(10., 50.), size2!(100., 30.)), red).with_gradient(
+                Gradient::linear(
+                    90.,
+                    [GradientStop::new(0., red), GradientStop::new(1., blue)],
+                )
+                .with_interpolation(GradientInterpolation::Perceptual),
+            ),
+        )
+        .with_quad(
+            Quad::new(Rect::new(point2!(10., 90.), size2!(60., 60.)), red)
+                .with_corner_radius(10.)
+                .with_border(2., Srgba::new(0., 0., 0., 1.))
+                .with_gradient(Gradient::radial(
+                    point2!(30., 30.),
+                    30.,
+                    [
+                        GradientStop::new(0., Srgba::new(1., 1., 0., 1.)),
+                        GradientStop::new(0.5, red),
+                        GradientStop::new(1., Srgba::new(1., 0., 0., 0.)),
+                    ],
+                )),
+        )
+        .with_quad(
+            Quad::new(Rect::new(point2!(80., 90.), size2!(30., 60.)), red).with_gradient(
+                Gradient::linear(
+                    0.,
+                    [
+                        GradientStop::new(0., Srgba::new(0., 0., 0., 0.5)),
+                        GradientStop::new(1., Srgba::new(0., 0., 0., 0.)),
+                    ],
+                ),
+            ),
+        );
+
+    assert_no_regressions(120, 160, scene);
+}
+
+#[test]
+fn shadows() {
+    let white = Srgba::new(1., 1., 1., 1.);
+    let shadow_color = Srgba::new(0., 0., 0., 0.5);
+    let region = Rect::new(point2!(20., 20.), size2!(60., 40.));
+    let inset_region = Rect::new(point2!(20., 90.), size2!(60., 40.));
+    let scene = Scene::new()
+        .with_clear(Srgba::new(0.9, 0.9, 0.9, 1.))
+        .with_shadow(
+            Shadow::new(region, shadow_color, 10.)
+                .with_corner_radius(8.)
+                .with_offset(vec2!(2., 4.)),
+        )
+        .with_quad(Quad::new(region, white).with_corner_radius(8.))
+        .with_quad(Quad::new(inset_region, white))
+        .with_shadow(
+            Shadow::new(inset_region, shadow_color, 6.)
+                .with_spread(2.)
+                .with_inset(true),
+        );
+
+    assert_no_regressions(100, 150, scene);
+}
+
+#[test]
+fn ellipses() {
+    let scene = Scene::new()
+        .with_clear(Srgba::new(1., 1., 1., 1.))
+        .with_ellipse(Ellipse::circle(
+            point2!(30., 30.),
+            20.,
+            Srgba::new(1., 0., 0., 1.),
+        ))
+        .with_ellipse(
+            Ellipse::new(
+                point2!(100., 30.),
+                vec2!(40., 15.),
+                Srgba::new(0., 0., 1., 0.5),
+            )
+            .with_stroke(3., Srgba::new(0., 0., 0., 1.)),
+        )
+        .with_ellipse(
+            Ellipse::new(
+                point2!(70., 75.),
+                vec2!(15., 20.),
+                Srgba::new(0., 0., 0., 0.),
+            )
+            .with_stroke(0.5, Srgba::new(0., 0.5, 0., 1.)),
+        );
+
+    assert_no_regressions(150, 100, scene);
+}
+
+#[test]
+fn line_segments() {
+    let black = Srgba::new(0., 0., 0., 1.);
+    let mut scene = Scene::new().with_clear(Srgba::new(1., 1., 1., 1.));
+    for (index, cap) in [LineCap::Butt, LineCap::Round, LineCap::Square]
+        .into_iter()
+        .enumerate()
+    {
+        let y = 20. + index as f32 * 20.;
+        scene.add_line_segment(
+            LineSegment::new(point2!(20., y), point2!(100., y), 8., black).with_cap(cap),
+        );
+    }
+    scene.add_line_segment(
+        LineSegment::new(
+            point2!(20., 120.),
+            point2!(100., 80.),
+            3.,
+            Srgba::new(1., 0., 0., 1.),
+        )
+        .with_cap(LineCap::Round),
+    );
+    scene.add_line_segment(LineSegment::new(
+        point2!(110., 10.),
+        point2!(110., 130.),
+        0.5,
+        Srgba::new(0., 0., 1., 1.),
+    ));
+
+    assert_no_regressions(120, 140, scene);
+}
+
+#[test]
+fn undercurls() {
+    let red = Srgba::new(1., 0., 0., 1.);
+    let mut scene = Scene::new().with_clear(Srgba::new(1., 1., 1., 1.));
+    // Drawn per cell like a terminal grid so the seams between cells are covered
+    for cell in 0..8 {
+        scene.add_undercurl(Undercurl::new(
+            point2!(10. + cell as f32 * 9., 20.),
+            9.,
+            1.,
+            6.,
+            2.,
+            red,
+        ));
+    }
+    scene.add_undercurl(Undercurl::new(
+        point2!(10., 50.),
+        72.,
+        2.,
+        12.,
+        3.,
+        Srgba::new(0., 0., 1., 1.),
+    ));
+
+    assert_no_regressions(100, 70, scene);
+}
+
 #[test]
 fn overlapping_quads() {
     let mut scene = Scene::new();
@@ -372,6 +778,63 @@ fn open_paths() {
     assert_no_regressions(200, 200, scene);
 }
 
+#[test]
+fn path_stroke_styles() {
+    let mut scene = Scene::new();
+
+    let styles = [
+        StrokeStyle::new()
+            .with_cap(LineCap::Butt)
+            .with_join(LineJoin::Miter),
+        StrokeStyle::new()
+            .with_cap(LineCap::Round)
+            .with_join(LineJoin::Round),
+        StrokeStyle::new()
+            .with_start_cap(LineCap::Square)
+            .with_end_cap(LineCap::Round)
+            .with_join(LineJoin::Bevel),
+        StrokeStyle::new()
+            .with_join(LineJoin::MiterClip)
+            .with_miter_limit(2.),
+    ];
+    for (i, style) in styles.into_iter().enumerate() {
+        let x = 30. + i as f32 * 45.;
+        scene.add_path(
+            Path::new_line(12., Srgba::new(0., 0., 0., 1.), point2!(x, 170.))
+                .with_line_to(point2!(x + 15., 30.))
+                .with_line_to(point2!(x + 30., 170.))
+                .with_stroke_style(style),
+        );
+    }
+    assert_no_regressions(200, 200, scene);
+}
+
+#[test]
+fn dashed_paths() {
+    let mut scene = Scene::new();
+
+    scene.add_path(
+        Path::new_line(4., Srgba::new(0., 0., 0., 1.), point2!(20., 20.))
+            .with_line_to(point2!(100., 20.))
+            .with_line_to(point2!(100., 80.))
+            .with_cubic_bezier_to(point2!(60., 140.), point2!(140., 140.), point2!(180., 40.))
+            .with_stroke_style(StrokeStyle::new().with_dashes([12., 6.], 3.)),
+    );
+    scene.add_path(
+        Path::new_stroke(4., Srgba::new(0., 0., 1., 1.), point2!(30., 120.))
+            .with_line_to(point2!(90., 120.))
+            .with_line_to(point2!(90., 180.))
+            .with_line_to(point2!(30., 180.))
+            .with_stroke_style(
+                StrokeStyle::new()
+                    .with_cap(LineCap::Round)
+                    .with_join(LineJoin::Round)
+                    .with_dashes([0., 10., 20.], 0.),
+            ),
+    );
+    assert_no_regressions(200, 200, scene);
+}
+
 #[test]
 fn simple_mask() {
     let mut scene = Scene::new();
@@ -0,0 +1,95 @@
+use lyon::{
+    math::point,
+    path::{Path, PathEvent},
+};
+
+use crate::{default_drawables::dash_path, StrokeStyle};
+
+fn horizontal_line(length: f32) -> Path {
+    let mut builder = Path::builder();
+    builder.begin(point(0., 0.));
+    builder.line_to(point(length, 0.));
+    builder.end(false);
+    builder.build()
+}
+
+// The start and end of each dash along the line
+fn dash_spans(style: &StrokeStyle, length: f32) -> Vec<(f32, f32)> {
+    let dashed = dash_path(&horizontal_line(length), style, 0.1).unwrap();
+    let mut spans = Vec::new();
+    let mut start = 0.;
+    for event in dashed.iter() {
+        match event {
+            PathEvent::Begin { at } => start = at.x,
+            PathEvent::End { last, .. } => spans.push((start, last.x)),
+            _ => {}
+        }
+    }
+    spans
+}
+
+fn assert_spans_eq(actual: &[(f32, f32)], expected: &[(f32, f32)]) {
+    let close = |a: f32, b: f32| (a - b).abs() < 0.001;
+    assert!(
+        actual.len() == expected.len()
+            && actual
+                .iter()
+                .zip(expected)
+                .all(|(a, e)| close(a.0, e.0) && close(a.1, e.1)),
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn solid_strokes_are_not_dashed() {
+    let line = horizontal_line(10.);
+    assert!(dash_path(&line, &StrokeStyle::new(), 0.1).is_none());
+    let zero_pattern = StrokeStyle::new().with_dashes([0., 0.], 0.);
+    assert!(dash_path(&line, &zero_pattern, 0.1).is_none());
+}
+
+#[test]
+fn patterns_finer_than_the_tolerance_are_not_dashed() {
+    let line = horizontal_line(100.);
+    let style = StrokeStyle::new().with_dashes([1e-6, 1e-6], 0.);
+    assert!(dash_path(&line, &style, 0.1).is_none());
+}
+
+#[test]
+fn dashes_per_sub_path_are_limited() {
+    // Each dash and gap is a little longer than the tolerance, so this would make half a million
+    // dashes and stop advancing once the distance travelled is large
+    let line = horizontal_line(1_000_000.);
+    let style = StrokeStyle::new().with_dashes([0.2, 0.2], 0.);
+    let dashed = dash_path(&line, &style, 0.1).unwrap();
+
+    let dashes = dashed
+        .iter()
+        .filter(|event| matches!(event, PathEvent::Begin { .. }))
+        .count();
+    assert!(dashes <= 5_001, "{}", dashes);
+    // The rest of the line is drawn solid
+    let end = dashed.iter().find_map(|event| match event {
+        PathEvent::End { last, .. } if last.x == 1_000_000. => Some(last),
+        _ => None,
+    });
+    assert!(end.is_some());
+}
+
+#[test]
+fn dashes_start_at_the_offset() {
+    let style = StrokeStyle::new().with_dashes([4., 2.], 1.);
+    assert_spans_eq(&dash_spans(&style, 12.), &[(0., 3.), (5., 9.), (11., 12.)]);
+}
+
+#[test]
+fn zero_length_first_dashes_are_kept() {
+    // Odd patterns repeat, so this is a dot, a dash, and a dash directly after it
+    let style = StrokeStyle::new().with_dashes([0., 10., 20.], 0.);
+    assert_spans_eq(
+        &dash_spans(&style, 60.),
+        &[(0., 0.), (10., 30.), (30., 40.)],
+    );
+}
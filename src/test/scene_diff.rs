@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use glamour::{point2, size2, Rect};
+use palette::Srgba;
+
+use crate::{scene::Scene, BatchDiff, Layer, LayerDiff, PrimitiveKind, Quad};
+
+fn quad(x: f32) -> Quad {
+    Quad::new(
+        Rect::new(point2!(x, 10.), size2!(50., 50.)),
+        Srgba::new(0., 0., 1., 1.),
+    )
+}
+
+#[test]
+fn shared_and_mutable_batches_with_equal_contents_are_equal() {
+    let mutable = Scene::new().with_quad(quad(10.)).with_quad(quad(20.));
+    let mut shared = Scene::new();
+    shared
+        .layer_mut()
+        .add_quads(Arc::new(vec![quad(10.), quad(20.)]));
+
+    assert!(mutable.diff(&shared).is_empty());
+}
+
+#[test]
+fn modified_primitives_are_counted() {
+    let old = Scene::new().with_quad(quad(10.)).with_quad(quad(20.));
+    let new = Scene::new().with_quad(quad(10.)).with_quad(quad(30.));
+
+    let diff = old.diff(&new);
+    assert_eq!(
+        diff.layers,
+        vec![LayerDiff::Modified {
+            index: 0,
+            clip: None,
+            mask: None,
+            batches: vec![BatchDiff::Modified {
+                index: 0,
+                old_kind: PrimitiveKind::Quads,
+                new_kind: PrimitiveKind::Quads,
+                old_len: 2,
+                new_len: 2,
+                changed_primitives: Some(1),
+            }],
+        }]
+    );
+    assert_eq!(
+        diff.to_string(),
+        "Layer 0: modified\n  batch 0: Quads (2) -> Quads (2), 1 primitives changed\n"
+    );
+}
+
+#[test]
+fn added_layers_and_clips_are_reported() {
+    let old = Scene::new().with_quad(quad(10.));
+    let new = Scene::new()
+        .with_quad(quad(10.))
+        .with_clip(Rect::new(point2!(0, 0), size2!(10, 10)))
+        .with_layer(Layer::new().with_quad(quad(20.)));
+
+    let diff = old.diff(&new);
+    assert!(matches!(
+        diff.layers[0],
+        LayerDiff::Modified { clip: Some(_), .. }
+    ));
+    assert_eq!(diff.layers[1], LayerDiff::Added { index: 1 });
+}
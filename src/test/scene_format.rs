@@ -0,0 +1,326 @@
+use std::time::Duration;
+
+use glamour::{point2, size2, Rect, Size2};
+use palette::Srgba;
+use serde_json::json;
+
+use crate::{
+    is_binary_scene, migrate_scene, scene::Scene, BorderWidths, CornerRadii, Quad,
+    ResourceDirectory, ResourceId, ResourceReference, ResourceResolver, Resources, SceneLoadError,
+    Synthesis, Texture, SCENE_FORMAT_VERSION,
+};
+
+#[test]
+fn scenes_serialize_with_format_version() {
+    let scene = Scene::new().with_quad(Quad::new(
+        Rect::new(point2!(10., 10.), size2!(50., 50.)),
+        Srgba::new(0., 0., 1., 1.),
+    ));
+
+    let document = serde_json::to_value(&scene).unwrap();
+    assert_eq!(document["format_version"], SCENE_FORMAT_VERSION);
+
+    let scene: Scene = serde_json::from_value(document).unwrap();
+    assert_eq!(scene.layer().contents.primitives.len(), 1);
+}
+
+#[test]
+fn checked_in_scene_still_loads() {
+    let scene: Scene = serde_json::from_str(include_str!("../../scene.json")).unwrap();
+    assert!(!scene.layers.is_empty());
+}
+
+#[test]
+fn unversioned_glyph_runs_get_default_synthesis() {
+    let document = json!({
+        "layers": [{
+            "primitives": [{
+                "GlyphRuns": [{
+                    "position": { "x": 0.0, "y": 10.0 },
+                    "font_id": 0,
+                    "font_index": 0,
+                    "color": { "red": 0.0, "green": 0.0, "blue": 0.0, "alpha": 1.0 },
+                    "size": 12.0,
+                    "normalized_coords": [],
+                    "glyphs": [{ "id": 22, "offset": { "x": 0.0, "y": 0.0 } }]
+                }]
+            }]
+        }],
+        "resources": { "fonts": {}, "textures": {} }
+    });
+
+    let scene: Scene = serde_json::from_value(document).unwrap();
+    let glyph_runs = scene.layer().contents.primitives[0]
+        .as_glyph_run_vec()
+        .unwrap();
+    assert_eq!(glyph_runs[0].synthesis, Synthesis::default());
+}
+
+#[test]
+fn newer_format_versions_are_rejected() {
+    let mut document = json!({
+        "format_version": SCENE_FORMAT_VERSION + 1,
+        "layers": [],
+        "resources": { "fonts": {}, "textures": {} }
+    });
+
+    assert!(migrate_scene(&mut document).is_err());
+}
+
+#[test]
+fn binary_scenes_round_trip() {
+    let scene: Scene = serde_json::from_str(include_str!("../../scene.json")).unwrap();
+
+    let mut binary = Vec::new();
+    scene.write_binary(&mut binary).unwrap();
+    assert!(is_binary_scene(&binary));
+
+    let json = serde_json::to_vec(&scene).unwrap();
+    assert!(binary.len() < json.len());
+
+    let decoded = Scene::read_binary(binary.as_slice()).unwrap();
+    assert_eq!(
+        serde_json::to_value(&decoded).unwrap(),
+        serde_json::to_value(&scene).unwrap()
+    );
+}
+
+#[test]
+fn truncated_binary_scenes_are_rejected() {
+    let mut binary = Vec::new();
+    Scene::new()
+        .with_quad(Quad::new(
+            Rect::new(point2!(10., 10.), size2!(50., 50.)),
+            Srgba::new(0., 0., 1., 1.),
+        ))
+        .write_binary(&mut binary)
+        .unwrap();
+
+    assert!(Scene::read_binary(&binary[..binary.len() - 1]).is_err());
+}
+
+#[test]
+fn checked_in_scene_loads_without_errors() {
+    Scene::from_bytes(include_bytes!("../../scene.json")).unwrap();
+}
+
+#[test]
+fn json_errors_report_their_position() {
+    let error = Scene::from_bytes(b"{\n  \"layers\": [,]\n}").unwrap_err();
+    assert!(matches!(error, SceneLoadError::Json { line: 2, .. }));
+}
+
+#[test]
+fn invalid_base64_is_reported() {
+    let document = json!({
+        "format_version": SCENE_FORMAT_VERSION,
+        "layers": [],
+        "resources": { "fonts": { "3": "not base64!" }, "textures": {} }
+    });
+
+    let error = Scene::from_bytes(document.to_string().as_bytes()).unwrap_err();
+    assert!(matches!(error, SceneLoadError::InvalidBase64 { .. }));
+}
+
+#[test]
+fn texture_size_mismatches_are_reported() {
+    let document = json!({
+        "format_version": SCENE_FORMAT_VERSION,
+        "layers": [],
+        "resources": {
+            "fonts": {},
+            "textures": { "0": { "data": "AAAA", "size": { "width": 2, "height": 2 } } }
+        }
+    });
+
+    let error = Scene::from_bytes(document.to_string().as_bytes()).unwrap_err();
+    assert!(matches!(
+        error,
+        SceneLoadError::TextureSizeMismatch { data_length: 3, .. }
+    ));
+}
+
+#[test]
+fn unknown_resources_are_reported() {
+    let document = json!({
+        "layers": [{
+            "primitives": [{
+                "GlyphRuns": [{
+                    "position": { "x": 0.0, "y": 10.0 },
+                    "font_id": 7,
+                    "font_index": 0,
+                    "color": { "red": 0.0, "green": 0.0, "blue": 0.0, "alpha": 1.0 },
+                    "size": 12.0,
+                    "normalized_coords": [],
+                    "glyphs": []
+                }]
+            }]
+        }],
+        "resources": { "fonts": {}, "textures": {} }
+    });
+
+    let error = Scene::from_bytes(document.to_string().as_bytes()).unwrap_err();
+    assert!(matches!(
+        error,
+        SceneLoadError::UnknownResource {
+            layer: 0,
+            resource: ResourceId::Font(_)
+        }
+    ));
+}
+
+#[test]
+fn shared_resources_are_stored_once_and_resolved() {
+    let scene: Scene = serde_json::from_str(include_str!("../../scene.json")).unwrap();
+    let directory = ResourceDirectory::new(
+        std::env::temp_dir().join(format!("vide-shared-resources-{}", std::process::id())),
+    );
+
+    let mut first = Vec::new();
+    scene
+        .write_json_with_shared_resources(&mut first, &directory)
+        .unwrap();
+    let mut second = Vec::new();
+    scene
+        .write_json_with_shared_resources(&mut second, &directory)
+        .unwrap();
+    assert_eq!(first, second);
+    assert_eq!(
+        std::fs::read_dir(&directory.root).unwrap().count(),
+        scene.resources.fonts.len()
+    );
+
+    assert!(matches!(
+        Scene::from_bytes(&first),
+        Err(SceneLoadError::UnresolvedResource { .. })
+    ));
+    let loaded = Scene::from_bytes_with_resolver(&first, &directory).unwrap();
+    assert_eq!(
+        serde_json::to_value(&loaded).unwrap(),
+        serde_json::to_value(&scene).unwrap()
+    );
+
+    std::fs::remove_dir_all(&directory.root).unwrap();
+}
+
+#[test]
+fn resource_paths_outside_the_directory_are_rejected() {
+    let directory = ResourceDirectory::new(std::env::temp_dir().join("vide-resources"));
+    for reference in [
+        ResourceReference::Path(std::env::temp_dir().join("font.ttf")),
+        ResourceReference::Path("../font.ttf".into()),
+        ResourceReference::Path("fonts/../../font.ttf".into()),
+        ResourceReference::Hash("../font".to_string()),
+    ] {
+        let error = directory.resolve(&reference).unwrap_err();
+        assert_ne!(
+            error.kind(),
+            std::io::ErrorKind::NotFound,
+            "{:?}",
+            reference
+        );
+    }
+}
+
+#[test]
+fn identical_textures_share_a_stable_id() {
+    let texture = Texture {
+        data: vec![255, 0, 0, 255],
+        size: Size2::new(1, 1),
+    };
+
+    let mut resources = Resources::default();
+    let first = resources.store_texture(texture.clone());
+    let second = resources.store_texture(texture);
+    assert_eq!(first, second);
+    assert_eq!(resources.textures.len(), 1);
+    assert_eq!(
+        serde_json::to_value(first).unwrap(),
+        json!(11170114672897998563u64)
+    );
+}
+
+#[test]
+fn recordings_write_each_resource_once() {
+    let scene: Scene = serde_json::from_str(include_str!("../../scene.json")).unwrap();
+    let font_bytes: usize = scene
+        .resources
+        .fonts
+        .values()
+        .map(|font| font.data.len())
+        .sum();
+    let path = std::env::temp_dir().join(format!("vide-recording-{}", std::process::id()));
+
+    let mut recorder = SceneRecorder::create(&path).unwrap();
+    recorder
+        .record_at(&scene, Duration::from_millis(0))
+        .unwrap();
+    recorder.flush().unwrap();
+    let first_frame_length = std::fs::metadata(&path).unwrap().len() as usize;
+    recorder
+        .record_at(&scene, Duration::from_millis(16))
+        .unwrap();
+    recorder.flush().unwrap();
+    let both_frames_length = std::fs::metadata(&path).unwrap().len() as usize;
+    assert!(both_frames_length - first_frame_length < first_frame_length - font_bytes);
+
+    let recording = SceneRecording::load(&path).unwrap();
+    assert_eq!(recording.frames.len(), 2);
+    assert_eq!(recording.frames[1].timestamp, Duration::from_millis(16));
+    assert!(recording.scene(1).diff(&scene).is_empty());
+    let mut replayed = Scene::new();
+    recording.load_frame(0, &mut replayed);
+    recording.load_frame(1, &mut replayed);
+    assert!(replayed.diff(&scene).is_empty());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn corner_radius_accepts_a_single_radius_or_one_per_corner() {
+    let quad = |corner_radius: serde_json::Value| {
+        json!({
+            "region": {
+                "origin": { "x": 0.0, "y": 0.0 },
+                "size": { "width": 10.0, "height": 10.0 },
+            },
+            "color": { "red": 1.0, "green": 0.0, "blue": 0.0, "alpha": 1.0 },
+            "corner_radius": corner_radius,
+        })
+    };
+
+    let uniform: Quad = serde_json::from_value(quad(json!(4.0))).unwrap();
+    assert_eq!(uniform.corner_radius, CornerRadii::uniform(4.));
+    assert_eq!(
+        serde_json::to_value(&uniform).unwrap()["corner_radius"],
+        4.0
+    );
+
+    let per_corner: Quad =
+        serde_json::from_value(quad(json!({ "top_left": 4.0, "top_right": 4.0 }))).unwrap();
+    assert_eq!(per_corner.corner_radius, CornerRadii::new(4., 4., 0., 0.));
+    assert_eq!(
+        serde_json::to_value(&per_corner).unwrap()["corner_radius"],
+        json!({ "top_left": 4.0, "top_right": 4.0, "bottom_right": 0.0, "bottom_left": 0.0 })
+    );
+}
+
+#[test]
+fn quad_borders_are_only_serialized_when_set() {
+    let quad = Quad::new(
+        Rect::new(point2!(0., 0.), size2!(10., 10.)),
+        Srgba::new(1., 0., 0., 1.),
+    );
+    assert!(serde_json::to_value(&quad).unwrap().get("border").is_none());
+
+    let quad = quad.with_border(
+        BorderWidths::new(1., 2., 3., 4.),
+        Srgba::new(0., 0., 0., 1.),
+    );
+    let document = serde_json::to_value(&quad).unwrap();
+    assert_eq!(
+        document["border"]["widths"],
+        json!({ "top": 1.0, "right": 2.0, "bottom": 3.0, "left": 4.0 })
+    );
+    assert_eq!(serde_json::from_value::<Quad>(document).unwrap(), quad);
+}
@@ -0,0 +1,243 @@
+use std::collections::BTreeSet;
+
+use glamour::{point2, size2, vec2, Rect, Size2};
+use palette::Srgba;
+use serde_json::{json, Value};
+
+use crate::{
+    check_scene_schema, migrate_scene, scene::Scene, scene_schema, Blur, BorderDash, Ellipse,
+    Gradient, GradientInterpolation, GradientStop, Layer, LineCap, LineJoin, LineSegment, Path,
+    Quad, SceneLoadError, Shadow, Sprite, StrokeStyle, Texture, TextureId, Transform, Undercurl,
+};
+
+#[test]
+fn serialized_scenes_match_the_schema() {
+    let mut document = serde_json::from_str(include_str!("../../scene.json")).unwrap();
+    migrate_scene(&mut document).unwrap();
+    check_scene_schema(&document).unwrap();
+
+    let color = Srgba::new(0., 0., 1., 1.);
+    let scene = Scene::new()
+        .with_clip(Rect::new(point2!(0, 0), size2!(100, 100)))
+        .with_mask(Layer::new().with_blur(Blur::new(point2!(0., 0.), size2!(10., 10.), color, 2.)))
+        .with_quad(Quad::new(
+            Rect::new(point2!(0., 0.), size2!(10., 10.)),
+            color,
+        ))
+        .with_quad(
+            Quad::new(Rect::new(point2!(0., 0.), size2!(10., 10.)), color)
+                .with_border(1., color)
+                .with_border_dash(BorderDash::dotted(4.))
+                .with_transform(Transform::scale(2., 1.))
+                .with_gradient(
+                    Gradient::radial(
+                        point2!(5., 5.),
+                        5.,
+                        [GradientStop::new(0., color), GradientStop::new(1., color)],
+                    )
+                    .with_interpolation(GradientInterpolation::Perceptual),
+                ),
+        )
+        .with_ellipse(Ellipse::circle(point2!(5., 5.), 5., color).with_stroke(1., color))
+        .with_line_segment(
+            LineSegment::new(point2!(0., 0.), point2!(10., 10.), 2., color)
+                .with_cap(LineCap::Square),
+        )
+        .with_undercurl(Undercurl::new(point2!(0., 5.), 10., 1., 4., 1., color))
+        .with_shadow(
+            Shadow::new(Rect::new(point2!(0., 0.), size2!(10., 10.)), color, 4.)
+                .with_offset(vec2!(1., 2.))
+                .with_inset(true),
+        )
+        .with_path(
+            Path::new_stroke(2., color, point2!(0., 0.))
+                .with_quadratic_bezier_to(point2!(5., 5.), point2!(10., 0.))
+                .with_cubic_bezier_to(point2!(10., 5.), point2!(5., 10.), point2!(0., 10.))
+                .with_stroke_style(
+                    StrokeStyle::new()
+                        .with_start_cap(LineCap::Round)
+                        .with_join(LineJoin::MiterClip)
+                        .with_miter_limit(3.)
+                        .with_dashes([2., 1.], 0.5),
+                ),
+        )
+        .with_sprite(Sprite::new(
+            Texture {
+                data: vec![255; 4],
+                size: Size2::new(1, 1),
+            },
+            point2!(0., 0.),
+            size2!(10., 10.),
+        ))
+        .with_sprite(
+            Sprite::new(
+                Texture {
+                    data: vec![255; 4],
+                    size: Size2::new(1, 1),
+                },
+                point2!(0., 0.),
+                size2!(10., 10.),
+            )
+            .with_transform(Transform::rotation(1.)),
+        );
+    check_scene_schema(&serde_json::to_value(&scene).unwrap()).unwrap();
+}
+
+// A layer holding a single primitive of the kind with every optional field set
+fn fully_populated_layer(kind: PrimitiveKind) -> Layer {
+    let color = Srgba::new(0., 0., 1., 1.);
+    let region = Rect::new(point2!(0., 0.), size2!(10., 10.));
+    match kind {
+        PrimitiveKind::Blurs => Layer::new().with_blur(
+            Blur::new(point2!(0., 0.), size2!(10., 10.), color, 2.).with_corner_radius(1.),
+        ),
+        PrimitiveKind::Quads => Layer::new().with_quad(
+            Quad::new(region, color)
+                .with_corner_radius(1.)
+                .with_edge_blur(1.)
+                .with_border(1., color)
+                .with_border_dash(BorderDash::dotted(4.))
+                .with_gradient(
+                    Gradient::linear(90., [GradientStop::new(0., color)])
+                        .with_interpolation(GradientInterpolation::Perceptual),
+                )
+                .with_transform(Transform::rotation(1.)),
+        ),
+        PrimitiveKind::GlyphRuns => {
+            let scene: Scene = serde_json::from_str(include_str!("../../scene.json")).unwrap();
+            let glyph_runs = scene
+                .layers
+                .iter()
+                .flat_map(|layer| layer.contents.primitives.iter())
+                .find(|batch| batch.is_glyph_runs())
+                .unwrap()
+                .clone();
+            let mut layer = Layer::new();
+            layer.contents.primitives.push(glyph_runs);
+            layer
+        }
+        PrimitiveKind::Paths => Layer::new().with_path(
+            Path::new(point2!(0., 0.))
+                .with_fill(color)
+                .with_stroke(1., color)
+                .with_stroke_style(StrokeStyle::new().with_dashes([2., 1.], 1.))
+                .with_line_to(point2!(10., 0.)),
+        ),
+        PrimitiveKind::Sprites => Layer::new().with_sprite(
+            Sprite::new(TextureId::new(1), point2!(0., 0.), size2!(10., 10.))
+                .with_color(color)
+                .with_transform(Transform::rotation(1.)),
+        ),
+        PrimitiveKind::Shadows => Layer::new().with_shadow(
+            Shadow::new(region, color, 4.)
+                .with_corner_radius(1.)
+                .with_offset(vec2!(1., 2.))
+                .with_spread(1.)
+                .with_inset(true),
+        ),
+        PrimitiveKind::Ellipses => Layer::new()
+            .with_ellipse(Ellipse::circle(point2!(5., 5.), 5., color).with_stroke(1., color)),
+        PrimitiveKind::LineSegments => Layer::new().with_line_segment(
+            LineSegment::new(point2!(0., 0.), point2!(10., 10.), 2., color)
+                .with_cap(LineCap::Round),
+        ),
+        PrimitiveKind::Undercurls => {
+            Layer::new().with_undercurl(Undercurl::new(point2!(0., 5.), 10., 1., 4., 1., color))
+        }
+    }
+}
+
+// Checks that a fully populated value has exactly the properties its schema lists, so neither the
+// schema nor the serialized type can gain a field the other lacks. Properties with several
+// possible shapes are skipped.
+fn assert_properties_match(schema: &Value, definition: &Value, value: &Value, pointer: &str) {
+    if let Some(reference) = definition.get("$ref").and_then(Value::as_str) {
+        let name = reference.trim_start_matches("#/$defs/");
+        return assert_properties_match(schema, &schema["$defs"][name], value, pointer);
+    }
+
+    if let (Some(properties), Some(fields)) = (
+        definition.get("properties").and_then(Value::as_object),
+        value.as_object(),
+    ) {
+        let expected: BTreeSet<_> = properties.keys().collect();
+        let actual: BTreeSet<_> = fields.keys().collect();
+        assert_eq!(
+            actual, expected,
+            "Fields of {} differ from the schema",
+            pointer
+        );
+        for (name, field) in fields {
+            let field_pointer = format!("{}/{}", pointer, name);
+            assert_properties_match(schema, &properties[name], field, &field_pointer);
+        }
+    }
+
+    if let (Some(items), Some(values)) = (definition.get("items"), value.as_array()) {
+        for (index, item) in values.iter().enumerate() {
+            assert_properties_match(schema, items, item, &format!("{}/{}", pointer, index));
+        }
+    }
+}
+
+#[test]
+fn schema_definitions_match_every_primitive() {
+    let schema = scene_schema();
+    let batches = schema["$defs"]["MutablePrimitiveBatch"]["oneOf"]
+        .as_array()
+        .unwrap();
+
+    let kinds = [
+        PrimitiveKind::Blurs,
+        PrimitiveKind::Quads,
+        PrimitiveKind::GlyphRuns,
+        PrimitiveKind::Paths,
+        PrimitiveKind::Sprites,
+        PrimitiveKind::Shadows,
+        PrimitiveKind::Ellipses,
+        PrimitiveKind::LineSegments,
+        PrimitiveKind::Undercurls,
+    ];
+    assert_eq!(batches.len(), kinds.len());
+    for kind in kinds {
+        let layer = serde_json::to_value(fully_populated_layer(kind)).unwrap();
+        let batch = &layer["primitives"][0];
+        let name = kind.to_string();
+        let definition = batches
+            .iter()
+            .find(|option| option["properties"].get(&name).is_some())
+            .unwrap_or_else(|| panic!("No schema for {} batches", name));
+        assert_properties_match(&schema, definition, batch, &name);
+    }
+}
+
+#[test]
+fn strict_loading_rejects_unknown_fields() {
+    let document = json!({
+        "layers": [{
+            "primitives": [{
+                "Quads": [{
+                    "region": {
+                        "origin": { "x": 0.0, "y": 0.0 },
+                        "size": { "width": 10.0, "height": 10.0 },
+                    },
+                    "color": { "red": 1.0, "green": 0.0, "blue": 0.0, "alpha": 1.0 },
+                    "edgeblur": 2.0,
+                }],
+            }],
+        }],
+        "resources": { "fonts": {}, "textures": {} },
+    });
+    let json = serde_json::to_vec(&document).unwrap();
+
+    assert!(Scene::from_bytes(&json).is_ok());
+    let error = Scene::from_bytes_strict(&json).unwrap_err();
+    assert!(matches!(
+        &error,
+        SceneLoadError::Schema { pointer, .. } if pointer == "/layers/0/primitives/0/Quads/0"
+    ));
+    assert_eq!(
+        error.to_string(),
+        "Invalid scene at /layers/0/primitives/0/Quads/0: Unknown field `edgeblur`"
+    );
+}
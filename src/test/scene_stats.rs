@@ -0,0 +1,78 @@
+use std::{mem::size_of, sync::Arc};
+
+use glamour::{point2, size2, Rect};
+use palette::Srgba;
+
+use crate::{default_drawables::InstancedQuad, scene::Scene, Layer, Path, Quad};
+
+#[test]
+fn stats_count_primitives_and_batches() {
+    let color = Srgba::new(1., 0., 0., 1.);
+    let quad = Quad::new(Rect::new(point2!(0., 0.), size2!(10., 10.)), color);
+    let mut scene = Scene::new()
+        .with_mask(Layer::new().with_quad(quad.clone()))
+        .with_quad(quad.clone())
+        .with_quad(quad.clone())
+        .with_layer(Layer::new());
+    scene
+        .layer_mut()
+        .add_paths(Arc::new(vec![Path::new_fill(color, point2!(0., 0.))
+            .with_line_to(point2!(10., 0.))
+            .with_line_to(point2!(10., 10.))]));
+
+    let stats = scene.stats();
+    assert_eq!(stats.primitives.quads, 3);
+    assert_eq!(stats.primitives.paths, 1);
+    assert_eq!(stats.batches, 3);
+    assert_eq!(stats.shared_batches, 1);
+    assert_eq!(stats.mutable_batches, 2);
+
+    assert_eq!(stats.layers.len(), 2);
+    assert_eq!(stats.layers[0].primitives.quads, 2);
+    assert_eq!(stats.layers[0].mask_primitives.quads, 1);
+    assert_eq!(stats.layers[1].primitives.paths, 1);
+
+    assert_eq!(stats.instance_bytes.quads, 3 * size_of::<InstancedQuad>());
+    assert!(stats.instance_bytes.paths > 0);
+}
+
+#[test]
+fn stats_count_unique_glyphs_and_resource_bytes() {
+    let scene: Scene = serde_json::from_str(include_str!("../../scene.json")).unwrap();
+    let stats = scene.stats();
+
+    assert!(!stats.glyphs.is_empty());
+    let unique_glyphs: usize = stats.glyphs.iter().map(|glyphs| glyphs.unique_glyphs).sum();
+    assert!(unique_glyphs <= stats.primitives.glyphs);
+    assert_eq!(
+        stats.font_bytes,
+        scene
+            .resources
+            .fonts
+            .values()
+            .map(|font| font.data.len())
+            .sum::<usize>()
+    );
+    assert!(stats.to_string().contains("Unique glyphs:"));
+}
+
+#[test]
+fn stats_skip_paths_which_cannot_be_tessellated() {
+    let color = Srgba::new(1., 0., 0., 1.);
+    let scene = Scene::new()
+        .with_path(
+            Path::new_fill(color, point2!(0., 0.))
+                .with_stroke(1., color)
+                .with_line_to(point2!(f32::NAN, 10.))
+                .with_line_to(point2!(10., 10.)),
+        )
+        .with_path(
+            Path::new_fill(color, point2!(0., 0.))
+                .with_line_to(point2!(10., 0.))
+                .with_line_to(point2!(10., 10.)),
+        );
+
+    let stats = scene.stats();
+    assert_eq!(stats.primitives.paths, 2);
+    assert!(stats.instance_bytes.paths > 0);
+}
@@ -0,0 +1,136 @@
+use glamour::{point2, size2, Rect};
+use palette::Srgba;
+
+use crate::{
+    scene::Scene, Gradient, GradientStop, Layer, Path, PrimitiveKind, Quad, SceneProblem,
+    SceneProblemKind, Sprite, TextureId, MAX_GRADIENT_STOPS,
+};
+
+fn invalid_scene() -> Scene {
+    let color = Srgba::new(1., 0., 0., 1.);
+    let mut open_fill = Path::new_line(1., color, point2!(0., 0.)).with_line_to(point2!(10., 10.));
+    open_fill.fill = Some(color);
+
+    Scene::new()
+        .with_quad(Quad::new(
+            Rect::new(point2!(0., 0.), size2!(10., 10.)),
+            color,
+        ))
+        .with_quad(Quad::new(
+            Rect::new(point2!(f32::NAN, 0.), size2!(10., 10.)),
+            color,
+        ))
+        .with_path(open_fill)
+        .with_layer(
+            Layer::new()
+                .with_mask(Layer::new().with_sprite(Sprite::new(
+                    TextureId::new(42),
+                    point2!(0., 0.),
+                    size2!(10., 10.),
+                )))
+                .with_quad(Quad::new(
+                    Rect::new(point2!(0., 0.), size2!(10., 10.)),
+                    color,
+                )),
+        )
+}
+
+#[test]
+fn validate_reports_problems_with_locations() {
+    assert_eq!(
+        invalid_scene().validate(),
+        vec![
+            SceneProblem {
+                layer: 0,
+                mask: false,
+                batch: 0,
+                primitive: 1,
+                kind: SceneProblemKind::NonFiniteValue,
+            },
+            SceneProblem {
+                layer: 0,
+                mask: false,
+                batch: 1,
+                primitive: 0,
+                kind: SceneProblemKind::OpenPathWithFill,
+            },
+            SceneProblem {
+                layer: 1,
+                mask: true,
+                batch: 0,
+                primitive: 0,
+                kind: SceneProblemKind::MissingTexture(TextureId::new(42)),
+            },
+        ]
+    );
+}
+
+#[test]
+fn missing_fonts_are_reported() {
+    let mut scene: Scene = serde_json::from_str(include_str!("../../scene.json")).unwrap();
+    assert!(scene.validate().is_empty());
+
+    scene.resources.fonts.clear();
+    let problems = scene.validate();
+    assert!(!problems.is_empty());
+    assert!(problems
+        .iter()
+        .all(|problem| matches!(problem.kind, SceneProblemKind::MissingFont(_))));
+}
+
+#[test]
+fn invalid_primitives_can_be_removed() {
+    let scene = invalid_scene();
+    let valid = scene.without_problems(&scene.validate());
+    assert!(valid.validate().is_empty());
+
+    let contents = &valid.layers[0].contents.primitives;
+    assert_eq!(contents[0].kind(), PrimitiveKind::Quads);
+    assert_eq!(contents[0].len(), 1);
+    assert!(contents[1].is_empty());
+    assert!(valid.layers[1].mask.as_ref().unwrap().primitives[0].is_empty());
+    assert_eq!(valid.layers[1].contents.primitives[0].len(), 1);
+    assert_eq!(
+        scene.validate()[0].to_string(),
+        "Layer 0 batch 0 primitive 1: contains a NaN or infinite value"
+    );
+}
+
+#[test]
+fn gradients_with_too_many_stops_are_reported() {
+    let color = Srgba::new(1., 0., 0., 1.);
+    let stops = (0..=MAX_GRADIENT_STOPS).map(|index| GradientStop::new(index as f32, color));
+    let scene = Scene::new().with_quad(
+        Quad::new(Rect::new(point2!(0., 0.), size2!(10., 10.)), color)
+            .with_gradient(Gradient::linear(90., stops)),
+    );
+
+    let problems = scene.validate();
+    assert_eq!(
+        problems[0].kind,
+        SceneProblemKind::TooManyGradientStops(MAX_GRADIENT_STOPS + 1)
+    );
+    assert_eq!(
+        problems[0].to_string(),
+        "Layer 0 batch 0 primitive 0: gradient has 5 stops but at most 4 are supported"
+    );
+}
+
+#[test]
+fn gradients_with_too_many_stops_are_not_drawn() {
+    let color = Srgba::new(1., 0., 0., 1.);
+    let quad = Quad::new(Rect::new(point2!(0., 0.), size2!(10., 10.)), color);
+    let stops = (0..MAX_GRADIENT_STOPS).map(|index| GradientStop::new(index as f32, color));
+    assert!(quad
+        .clone()
+        .with_gradient(Gradient::linear(90., stops))
+        .to_instanced()
+        .is_some());
+
+    // Skipped even though the scene isn't validated while rendering
+    let stops = (0..=MAX_GRADIENT_STOPS).map(|index| GradientStop::new(index as f32, color));
+    assert!(quad
+        .with_gradient(Gradient::linear(90., stops))
+        .to_instanced()
+        .is_none());
+}
@@ -0,0 +1,287 @@
+use glamour::{point2, size2, vec2, Rect};
+use palette::Srgba;
+
+use crate::{
+    scene::Scene, BorderColors, BorderDash, Ellipse, Gradient, GradientStop, Layer, LineCap,
+    LineJoin, LineSegment, Path, Quad, Shadow, StrokeStyle, Transform, Undercurl,
+};
+
+#[test]
+fn quads_and_paths_export_as_svg_shapes() {
+    let scene = Scene::new()
+        .with_clear(Srgba::new(1., 1., 1., 1.))
+        .with_quad(
+            Quad::new(
+                Rect::new(point2!(10., 10.), size2!(50., 40.)),
+                Srgba::new(0., 0., 1., 0.5),
+            )
+            .with_corner_radius(5.),
+        )
+        .with_path(
+            Path::new_fill(Srgba::new(1., 0., 0., 1.), point2!(70., 10.))
+                .with_line_to(point2!(100., 50.))
+                .with_line_to(point2!(70., 50.)),
+        );
+
+    let svg = scene.to_svg();
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50""#));
+    assert!(svg.contains(r##"<rect x="0" y="0" width="100" height="50" rx="0" fill="#ffffff"/>"##));
+    assert!(svg.contains(
+        r##"<rect x="10" y="10" width="50" height="40" rx="5" fill="#0000ff" fill-opacity="0.5"/>"##
+    ));
+    assert!(svg.contains(r##"<path d="M70 10 L100 50 L70 50 Z" fill="#ff0000" stroke="none"/>"##));
+}
+
+#[test]
+fn path_stroke_styles_export_as_stroke_attributes() {
+    let scene = Scene::new().with_path(
+        Path::new_line(4., Srgba::new(0., 0., 0., 1.), point2!(0., 0.))
+            .with_line_to(point2!(10., 10.))
+            .with_line_to(point2!(20., 0.))
+            .with_stroke_style(
+                StrokeStyle::new()
+                    .with_cap(LineCap::Round)
+                    .with_join(LineJoin::Bevel)
+                    .with_miter_limit(2.),
+            ),
+    );
+
+    let svg = scene.to_svg();
+    assert!(svg.contains(
+        r##"<path d="M0 0 L10 10 L20 0" fill="none" stroke="#000000" stroke-width="4" stroke-linecap="round" stroke-linejoin="bevel" stroke-miterlimit="2"/>"##
+    ));
+}
+
+#[test]
+fn different_path_caps_export_as_markers() {
+    let scene = Scene::new().with_path(
+        Path::new_line(4., Srgba::new(0., 0., 0., 1.), point2!(0., 0.))
+            .with_line_to(point2!(20., 0.))
+            .with_stroke_style(StrokeStyle::new().with_end_cap(LineCap::Round)),
+    );
+
+    let svg = scene.to_svg();
+    assert!(svg.contains(r#" stroke-linecap="butt""#));
+    assert!(svg.contains(r#"<marker id="cap-0-end""#));
+    assert!(svg.contains(r#" marker-end="url(#cap-0-end)"/>"#));
+    // The default butt start cap needs no marker
+    assert!(!svg.contains("marker-start"));
+}
+
+#[test]
+fn path_dashes_export_as_dash_arrays() {
+    let scene = Scene::new().with_path(
+        Path::new_line(2., Srgba::new(0., 0., 0., 1.), point2!(0., 0.))
+            .with_line_to(point2!(20., 0.))
+            .with_stroke_style(StrokeStyle::new().with_dashes([4., 2.5], -1.)),
+    );
+
+    let svg = scene.to_svg();
+    assert!(svg.contains(r#" stroke-dasharray="4 2.5" stroke-dashoffset="-1"/>"#));
+}
+
+#[test]
+fn quad_borders_export_as_rings() {
+    let scene = Scene::new()
+        .with_quad(
+            Quad::new(
+                Rect::new(point2!(0., 0.), size2!(20., 20.)),
+                Srgba::new(0., 0., 1., 1.),
+            )
+            .with_border(2., Srgba::new(1., 0., 0., 1.)),
+        )
+        .with_quad(
+            Quad::new(
+                Rect::new(point2!(30., 0.), size2!(20., 20.)),
+                Srgba::new(0., 0., 1., 1.),
+            )
+            .with_border(
+                2.,
+                BorderColors::new(
+                    Srgba::new(1., 0., 0., 1.),
+                    Srgba::new(0., 1., 0., 1.),
+                    Srgba::new(1., 0., 0., 1.),
+                    Srgba::new(0., 1., 0., 1.),
+                ),
+            ),
+        );
+
+    let svg = scene.to_svg();
+    // The fill only covers the area inside the border
+    assert!(svg.contains(r##"<rect x="2" y="2" width="16" height="16" rx="0" fill="#0000ff"/>"##));
+    assert!(svg.contains(r##"fill-rule="evenodd" fill="#ff0000"/>"##));
+    assert!(svg.contains(
+        r#"<clipPath id="border-1"><polygon points="50 0 50 20 48 18 48 2"/></clipPath>"#
+    ));
+    assert_eq!(svg.matches(r#"clip-path="url(#border-"#).count(), 4);
+}
+
+#[test]
+fn dashed_borders_export_as_dashed_strokes() {
+    let scene = Scene::new().with_quad(
+        Quad::new(
+            Rect::new(point2!(0., 0.), size2!(22., 22.)),
+            Srgba::new(0., 0., 0., 0.),
+        )
+        .with_border(2., Srgba::new(0., 0., 0., 1.))
+        .with_border_dash(BorderDash::dotted(8.).with_phase(2.)),
+    );
+
+    let svg = scene.to_svg();
+    assert!(svg.contains(
+        r##"<path d="M1 1 H21 A0 0 0 0 1 21 1 V21 A0 0 0 0 1 21 21 H1 A0 0 0 0 1 1 21 V1 A0 0 0 0 1 1 1 Z" fill="none" stroke="#000000" stroke-width="2" stroke-dasharray="0 8" stroke-dashoffset="-2" stroke-linecap="round"/>"##
+    ));
+}
+
+#[test]
+fn quad_gradients_export_as_svg_gradients() {
+    let scene = Scene::new().with_quad(
+        Quad::new(
+            Rect::new(point2!(0., 0.), size2!(20., 10.)),
+            Srgba::new(0., 0., 0., 1.),
+        )
+        .with_gradient(Gradient::radial(
+            point2!(10., 5.),
+            10.,
+            [
+                GradientStop::new(0., Srgba::new(1., 0., 0., 1.)),
+                GradientStop::new(1., Srgba::new(0., 0., 1., 0.5)),
+            ],
+        )),
+    );
+
+    let svg = scene.to_svg();
+    assert!(svg.contains(
+        r#"<radialGradient id="gradient-0" gradientUnits="userSpaceOnUse" cx="10" cy="5" r="10" color-interpolation="linearRGB">"#
+    ));
+    assert!(svg.contains(r##"<stop offset="1" stop-color="#0000ff" stop-opacity="0.5"/>"##));
+    assert!(svg.contains(r#"fill="url(#gradient-0)"/>"#));
+}
+
+#[test]
+fn shadows_export_as_blur_filters() {
+    let scene = Scene::new().with_shadow(
+        Shadow::new(
+            Rect::new(point2!(10., 10.), size2!(20., 20.)),
+            Srgba::new(0., 0., 0., 0.5),
+            4.,
+        )
+        .with_spread(2.),
+    );
+
+    let svg = scene.to_svg();
+    assert!(svg.contains(
+        r#"<filter id="shadow-0" filterUnits="userSpaceOnUse" x="2" y="2" width="36" height="36"><feGaussianBlur stdDeviation="2"/></filter>"#
+    ));
+    assert!(svg.contains(r#"<clipPath id="shadow-clip-0">"#));
+    assert!(svg.contains(r#"filter="url(#shadow-0)" clip-path="url(#shadow-clip-0)""#));
+}
+
+#[test]
+fn ellipses_export_as_ellipse_elements() {
+    let scene = Scene::new().with_ellipse(
+        Ellipse::new(
+            point2!(20., 10.),
+            vec2!(10., 5.),
+            Srgba::new(0., 0., 1., 1.),
+        )
+        .with_stroke(2., Srgba::new(1., 0., 0., 1.)),
+    );
+
+    let svg = scene.to_svg();
+    assert!(svg.contains(r#"width="31" height="16""#));
+    assert!(svg.contains(
+        r##"<ellipse cx="20" cy="10" rx="10" ry="5" fill="#0000ff" stroke="#ff0000" stroke-width="2"/>"##
+    ));
+}
+
+#[test]
+fn line_segments_export_as_lines() {
+    let scene = Scene::new().with_line_segment(
+        LineSegment::new(
+            point2!(10., 10.),
+            point2!(30., 10.),
+            4.,
+            Srgba::new(1., 0., 0., 1.),
+        )
+        .with_cap(LineCap::Round),
+    );
+
+    let svg = scene.to_svg();
+    assert!(svg.contains(r#"width="32" height="12""#));
+    assert!(svg.contains(
+        r##"<line x1="10" y1="10" x2="30" y2="10" stroke="#ff0000" stroke-width="4" stroke-linecap="round"/>"##
+    ));
+}
+
+#[test]
+fn undercurls_export_as_polylines() {
+    let scene = Scene::new().with_undercurl(Undercurl::new(
+        point2!(0., 10.),
+        8.,
+        1.,
+        8.,
+        2.,
+        Srgba::new(1., 0., 0., 1.),
+    ));
+
+    let svg = scene.to_svg();
+    assert!(svg.contains(r#"width="8" height="13""#));
+    assert!(svg.contains(r#"<path d="M0 10 L1 "#));
+    assert!(svg.contains(r##" L8 10" fill="none" stroke="#ff0000" stroke-width="1"/>"##));
+}
+
+#[test]
+fn clips_and_masks_export_as_defs() {
+    let scene = Scene::new()
+        .with_clip(Rect::new(point2!(0, 0), size2!(20, 20)))
+        .with_mask(Layer::new().with_quad(Quad::new(
+            Rect::new(point2!(0., 0.), size2!(10., 10.)),
+            Srgba::new(0., 0., 0., 1.),
+        )))
+        .with_quad(Quad::new(
+            Rect::new(point2!(0., 0.), size2!(30., 30.)),
+            Srgba::new(0., 1., 0., 1.),
+        ));
+
+    let svg = scene.to_svg();
+    assert!(svg.contains(
+        r#"<clipPath id="clip-0"><rect x="0" y="0" width="20" height="20"/></clipPath>"#
+    ));
+    assert!(svg.contains(r#"<mask id="mask-0" style="mask-type:alpha""#));
+    assert!(svg.contains(r#"<g clip-path="url(#clip-0)" mask="url(#mask-0)">"#));
+}
+
+#[test]
+fn transformed_quads_export_in_groups() {
+    let scene = Scene::new().with_quad(
+        Quad::new(
+            Rect::new(point2!(0., 0.), size2!(10., 10.)),
+            Srgba::new(0., 0., 1., 1.),
+        )
+        .with_border(1., Srgba::new(1., 0., 0., 1.))
+        .with_transform(Transform::scale(2., 1.).then(&Transform::translation(vec2!(5., 0.)))),
+    );
+
+    let svg = scene.to_svg();
+    assert!(svg.contains(r#"width="25" height="10""#));
+    assert!(svg.contains(r#"<g transform="matrix(2 0 0 1 5 0)">"#));
+    assert!(svg.contains(r##"<rect x="1" y="1" width="8" height="8" rx="0" fill="#0000ff"/>"##));
+    assert!(svg.contains("</g>"));
+}
+
+#[test]
+fn glyph_runs_export_as_outlines() {
+    let scene: Scene = serde_json::from_str(include_str!("../../scene.json")).unwrap();
+    let glyph_runs = scene
+        .layers
+        .iter()
+        .flat_map(|layer| layer.contents.primitives.iter())
+        .filter(|batch| batch.is_glyph_runs())
+        .count();
+    assert!(glyph_runs > 0);
+
+    let svg = scene.to_svg();
+    assert!(svg.contains("<path d=\"M"));
+    assert!(svg.contains('Z'));
+}
@@ -0,0 +1,189 @@
+use glamour::point2;
+use palette::Srgba;
+
+use crate::{Path, PathCommand};
+
+#[test]
+fn relative_commands_and_shorthands_resolve_to_absolute_points() {
+    let paths = Path::from_svg_path_data("M10 10 h20 v20 H10 Z").unwrap();
+    assert_eq!(
+        paths,
+        vec![Path::new(point2!(10., 10.))
+            .with_line_to(point2!(30., 10.))
+            .with_line_to(point2!(30., 30.))
+            .with_line_to(point2!(10., 30.))]
+    );
+
+    let paths =
+        Path::from_svg_path_data("M0,0 C0,10 10,10 10,0 s10-10 10,0 Q25-5 30,0 T40,0").unwrap();
+    assert_eq!(paths.len(), 1);
+    assert!(paths[0].open);
+    assert_eq!(
+        paths[0].commands[1],
+        PathCommand::CubicBezierTo {
+            control1: point2!(10., -10.),
+            control2: point2!(20., -10.),
+            to: point2!(20., 0.),
+        }
+    );
+    assert_eq!(
+        paths[0].commands[3],
+        PathCommand::QuadraticBezierTo {
+            control: point2!(35., 5.),
+            to: point2!(40., 0.),
+        }
+    );
+}
+
+#[test]
+fn each_subpath_becomes_a_path() {
+    let paths = Path::from_svg_path_data("M0 0 10 0 10 10z m20 0 l5 5").unwrap();
+    assert_eq!(paths.len(), 2);
+    assert!(!paths[0].open);
+    assert_eq!(paths[0].commands.len(), 2);
+    assert!(paths[1].open);
+    assert_eq!(paths[1].start, point2!(20., 0.));
+    assert_eq!(
+        paths[1].commands,
+        vec![PathCommand::LineTo {
+            to: point2!(25., 5.)
+        }]
+    );
+}
+
+#[test]
+fn arcs_become_cubic_beziers() {
+    let paths = Path::from_svg_path_data("M0 0 A10 10 0 0 1 20 0").unwrap();
+    let commands = &paths[0].commands;
+    assert_eq!(commands.len(), 2);
+
+    let middle = commands[0].to();
+    assert!((middle.x - 10.).abs() < 0.001 && (middle.y + 10.).abs() < 0.001);
+    assert_eq!(commands[1].to(), point2!(20., 0.));
+}
+
+#[test]
+fn svg_elements_load_with_fill_and_stroke() {
+    let svg = r##"<?xml version="1.0"?>
+        <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+            <!-- <rect width="1" height="1"/> -->
+            <rect x="10" y="10" width="20" height="10" fill="#ff0000"/>
+            <circle cx="50" cy="50" r="10" fill="none" stroke="blue" stroke-width="2"/>
+            <ellipse cx="50" cy="50" rx="20" ry="10" style="fill: #0f0; fill-opacity: 0.5"/>
+            <polygon points="0,0 10,0 5,10" fill="white" stroke="black"/>
+            <path d="M0 0 L10 10" fill="none"/>
+            <text x="0" y="0">ignored</text>
+        </svg>"##;
+    let paths = Path::from_svg(svg).unwrap();
+    assert_eq!(paths.len(), 4);
+
+    assert_eq!(paths[0].fill, Some(Srgba::new(1., 0., 0., 1.)));
+    assert_eq!(paths[0].stroke, None);
+    assert_eq!(paths[1].fill, None);
+    assert_eq!(paths[1].stroke, Some((2., Srgba::new(0., 0., 1., 1.))));
+    assert_eq!(paths[2].fill, Some(Srgba::new(0., 1., 0., 0.5)));
+    assert_eq!(paths[3].fill, Some(Srgba::new(1., 1., 1., 1.)));
+    assert_eq!(paths[3].stroke, Some((1., Srgba::new(0., 0., 0., 1.))));
+    assert_eq!(paths[3].commands.len(), 2);
+}
+
+#[test]
+fn subpaths_cutting_holes_are_filled_as_one_path() {
+    let svg = r#"<svg>
+            <path d="M0 0 H30 V30 H0 Z M10 10 V20 H20 V10 Z" fill="red" stroke="blue"/>
+        </svg>"#;
+    let paths = Path::from_svg(svg).unwrap();
+    assert_eq!(paths.len(), 3);
+
+    // The inner square is reached by a bridge from the end of the outer one and back
+    assert!(!paths[0].open && paths[0].stroke.is_none());
+    assert_eq!(paths[0].commands.len(), 3 + 1 + 3 + 2);
+    assert_eq!(
+        paths[0].commands[3],
+        PathCommand::LineTo {
+            to: point2!(10., 10.)
+        }
+    );
+    assert_eq!(
+        paths[0].commands.last(),
+        Some(&PathCommand::LineTo {
+            to: point2!(0., 30.)
+        })
+    );
+    assert!(paths[1..]
+        .iter()
+        .all(|path| path.fill.is_none() && path.stroke.is_some()));
+}
+
+#[test]
+fn overlapping_subpaths_follow_the_fill_rule() {
+    let data = "M0 0 H20 V20 H0 Z M10 10 H30 V30 H10 Z";
+    let svg = format!(r#"<svg><path d="{}" fill="red"/></svg>"#, data);
+    let nonzero = Path::from_svg(&svg).unwrap();
+    // Both squares wind the same way, so filling them separately keeps their overlap filled
+    assert_eq!(nonzero.len(), 2);
+    assert!(nonzero.iter().all(|path| !path.open && path.fill.is_some()));
+
+    let svg = format!(
+        r#"<svg><path d="{}" fill="red" fill-rule="evenodd"/></svg>"#,
+        data
+    );
+    let even_odd = Path::from_svg(&svg).unwrap();
+    assert_eq!(even_odd.len(), 1);
+}
+
+#[test]
+fn unknown_colors_fall_back_to_the_default_paint() {
+    let svg = r##"<svg>
+            <rect width="10" height="10" fill="currentColor"/>
+            <rect width="10" height="10" fill="#ff000080" stroke="rgba(0, 0, 255, 0.5)"/>
+            <rect width="10" height="10" fill="cornflowerblue" stroke="var(--accent)"/>
+        </svg>"##;
+    let paths = Path::from_svg(svg).unwrap();
+    assert_eq!(paths.len(), 3);
+
+    let black = Srgba::new(0., 0., 0., 1.);
+    assert_eq!(paths[0].fill, Some(black));
+    assert_eq!(paths[1].fill, Some(Srgba::new(1., 0., 0., 128. / 255.)));
+    assert_eq!(paths[1].stroke, Some((1., Srgba::new(0., 0., 1., 0.5))));
+    assert_eq!(paths[2].fill, Some(black));
+    assert_eq!(paths[2].stroke, None);
+}
+
+#[test]
+fn invalid_rect_sizes_are_errors() {
+    for size in [
+        r#"width="-10" height="10""#,
+        r#"width="NaN" height="10""#,
+        r#"width="10" height="inf""#,
+    ] {
+        let svg = format!(r#"<svg><rect x="0" y="0" {} rx="2"/></svg>"#, size);
+        assert!(Path::from_svg(&svg).is_err(), "{}", size);
+    }
+}
+
+#[test]
+fn stroke_widths_are_lengths_and_opacities_are_clamped() {
+    let svg = r#"<svg>
+            <rect width="10" height="10" stroke="blue" stroke-width="2px" fill-opacity="1.5" stroke-opacity="-1"/>
+        </svg>"#;
+    let paths = Path::from_svg(svg).unwrap();
+    assert_eq!(paths[0].fill, Some(Srgba::new(0., 0., 0., 1.)));
+    assert_eq!(paths[0].stroke, Some((2., Srgba::new(0., 0., 1., 0.))));
+
+    for style in [
+        r#"stroke-width="-1""#,
+        r#"stroke-width="NaN""#,
+        r#"opacity="inf""#,
+        r#"fill-opacity="NaN""#,
+    ] {
+        let svg = format!(r#"<svg><rect width="10" height="10" {}/></svg>"#, style);
+        assert!(Path::from_svg(&svg).is_err(), "{}", style);
+    }
+}
+
+#[test]
+fn invalid_path_data_reports_the_offset() {
+    let error = Path::from_svg_path_data("M0 0 L10 x").unwrap_err();
+    assert_eq!(error.offset, 9);
+}
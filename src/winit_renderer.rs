@@ -1,8 +1,10 @@
 use std::sync::Arc;
+
+use log::warn;
 use wgpu::*;
 use winit::window::Window;
 
-use crate::{drawable::Drawable, Renderer, Scene};
+use crate::{drawable::Drawable, Renderer, Scene, SceneRecorder};
 
 pub struct WinitRenderer {
     pub instance: Instance,
@@ -10,6 +12,7 @@ pub struct WinitRenderer {
     pub surface_config: SurfaceConfiguration,
     pub window: Arc<Window>,
     renderer: Renderer,
+    recorder: Option<SceneRecorder>,
 }
 
 impl WinitRenderer {
@@ -66,6 +69,7 @@ impl WinitRenderer {
             surface_config,
             renderer,
             window,
+            recorder: None,
         }
     }
 
@@ -87,6 +91,17 @@ impl WinitRenderer {
         self
     }
 
+    /// Skips primitives which fail `Scene::validate` when drawing. See
+    /// `Renderer::validate_scenes`.
+    pub fn set_scene_validation(&mut self, validate: bool) {
+        self.renderer.validate_scenes = validate;
+    }
+
+    pub fn with_scene_validation(mut self, validate: bool) -> Self {
+        self.set_scene_validation(validate);
+        self
+    }
+
     pub fn resize(&mut self, new_width: u32, new_height: u32) {
         self.surface_config.width = new_width;
         self.surface_config.height = new_height;
@@ -111,7 +126,25 @@ impl WinitRenderer {
         self.clear_surface();
     }
 
+    /// Records every scene passed to `draw` with the given recorder until `stop_recording` is
+    /// called.
+    pub fn start_recording(&mut self, recorder: SceneRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    pub fn stop_recording(&mut self) -> Option<SceneRecorder> {
+        self.recorder.take()
+    }
+
     pub fn draw(&mut self, scene: &Scene) -> bool {
+        if let Some(recorder) = &mut self.recorder {
+            profiling::scope!("record scene");
+            if let Err(error) = recorder.record(scene) {
+                warn!("Could not record scene, stopping recording: {}", error);
+                self.recorder = None;
+            }
+        }
+
         let Some(surface) = &mut self.surface else {
             return true;
         };